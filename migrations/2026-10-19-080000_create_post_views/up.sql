@@ -0,0 +1,19 @@
+CREATE TABLE post_views (
+    id SERIAL PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    visitor_hash VARCHAR(64) NOT NULL,
+    viewed_on DATE NOT NULL DEFAULT CURRENT_DATE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (post_id, visitor_hash, viewed_on),
+    FOREIGN KEY (post_id) REFERENCES posts(id)
+);
+
+CREATE INDEX idx_post_views_viewed_on ON post_views (viewed_on);
+
+CREATE TABLE post_daily_views (
+    post_id INTEGER NOT NULL,
+    view_date DATE NOT NULL,
+    views INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (post_id, view_date),
+    FOREIGN KEY (post_id) REFERENCES posts(id)
+);
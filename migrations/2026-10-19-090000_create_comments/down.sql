@@ -0,0 +1,3 @@
+DELETE FROM settings WHERE param = 'comment_default_status';
+
+DROP TABLE IF EXISTS comments;
@@ -0,0 +1 @@
+DELETE FROM settings WHERE param IN ('site_title', 'site_description', 'site_url', 'post_url_pattern', 'feed_size');
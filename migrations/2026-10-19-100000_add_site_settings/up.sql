@@ -0,0 +1,7 @@
+INSERT INTO settings (param, value, note) VALUES
+    ('site_title', 'Sedikit Acak', 'Title used by feeds'),
+    ('site_description', 'Sedikit Acak', 'Description used by feeds'),
+    ('site_url', 'https://sedikitacak.com', 'Frontend base URL without trailing slash'),
+    ('post_url_pattern', '{site_url}/post/{slug}', 'Frontend URL of a post'),
+    ('feed_size', '20', 'Number of posts listed in feeds')
+ON CONFLICT (param) DO NOTHING;
@@ -0,0 +1 @@
+DELETE FROM settings WHERE param IN ('category_url_pattern', 'project_url_pattern', 'sitemap_static_pages');
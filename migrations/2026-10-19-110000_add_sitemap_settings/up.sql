@@ -0,0 +1,5 @@
+INSERT INTO settings (param, value, note) VALUES
+    ('category_url_pattern', '{site_url}/category/{slug}', 'Frontend URL of a post category'),
+    ('project_url_pattern', '{site_url}/project/{id}', 'Frontend URL of a project'),
+    ('sitemap_static_pages', '/,/blog,/projects,/contact', 'Comma separated static paths listed in the sitemap')
+ON CONFLICT (param) DO NOTHING;
@@ -0,0 +1,10 @@
+CREATE TABLE post_slugs (
+    id SERIAL PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    slug VARCHAR(255) NOT NULL UNIQUE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    FOREIGN KEY (post_id) REFERENCES posts(id)
+);
+
+INSERT INTO post_slugs (post_id, slug, created_at)
+SELECT id, slug, created_at FROM posts;
@@ -0,0 +1,19 @@
+CREATE TABLE series (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    slug VARCHAR(255) NOT NULL UNIQUE,
+    description TEXT,
+    published BOOLEAN DEFAULT FALSE NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    deleted_at TIMESTAMP
+);
+
+CREATE TABLE series_posts (
+    series_id INTEGER NOT NULL,
+    post_id INTEGER NOT NULL UNIQUE,
+    part_order INTEGER NOT NULL,
+    PRIMARY KEY (series_id, post_id),
+    FOREIGN KEY (series_id) REFERENCES series(id),
+    FOREIGN KEY (post_id) REFERENCES posts(id)
+);
@@ -0,0 +1,14 @@
+CREATE TABLE post_authors (
+    post_id INTEGER NOT NULL,
+    user_id UUID NOT NULL,
+    role VARCHAR(20) DEFAULT 'author' NOT NULL CHECK (role IN ('author', 'editor', 'reviewer')),
+    author_order INTEGER NOT NULL,
+    PRIMARY KEY (post_id, user_id),
+    FOREIGN KEY (post_id) REFERENCES posts(id),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX post_authors_user_id_idx ON post_authors (user_id);
+
+INSERT INTO post_authors (post_id, user_id, role, author_order)
+SELECT id, author_id, 'author', 1 FROM posts;
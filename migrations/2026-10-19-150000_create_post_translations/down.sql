@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS post_translations;
+DELETE FROM settings WHERE param = 'default_locale';
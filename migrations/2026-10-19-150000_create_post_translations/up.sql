@@ -0,0 +1,17 @@
+CREATE TABLE post_translations (
+    id SERIAL PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    locale VARCHAR(10) NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    subtitle VARCHAR(255),
+    slug VARCHAR(255) NOT NULL UNIQUE,
+    content TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    UNIQUE (post_id, locale),
+    FOREIGN KEY (post_id) REFERENCES posts(id)
+);
+
+INSERT INTO settings (param, value, note) VALUES
+    ('default_locale', 'id', 'Locale of the original post content, used when no translation matches')
+ON CONFLICT (param) DO NOTHING;
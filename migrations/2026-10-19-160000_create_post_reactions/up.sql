@@ -0,0 +1,9 @@
+CREATE TABLE post_reactions (
+    id SERIAL PRIMARY KEY,
+    post_id INTEGER NOT NULL,
+    reaction VARCHAR(20) NOT NULL CHECK (reaction IN ('like', 'clap', 'insightful')),
+    visitor_hash VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    UNIQUE (post_id, reaction, visitor_hash),
+    FOREIGN KEY (post_id) REFERENCES posts(id)
+);
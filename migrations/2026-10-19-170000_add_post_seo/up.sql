@@ -0,0 +1,6 @@
+ALTER TABLE posts
+    ADD COLUMN cover_image VARCHAR(255),
+    ADD COLUMN meta_title VARCHAR(70),
+    ADD COLUMN meta_description VARCHAR(160),
+    ADD COLUMN canonical_url VARCHAR(255),
+    ADD COLUMN og_image VARCHAR(255);
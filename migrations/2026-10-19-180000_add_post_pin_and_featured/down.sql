@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS posts_featured_order_idx;
+
+ALTER TABLE posts
+    DROP COLUMN IF EXISTS pin_scope,
+    DROP COLUMN IF EXISTS pinned_until,
+    DROP COLUMN IF EXISTS featured,
+    DROP COLUMN IF EXISTS featured_order;
@@ -0,0 +1,7 @@
+ALTER TABLE posts
+    ADD COLUMN pin_scope VARCHAR(20),
+    ADD COLUMN pinned_until TIMESTAMP,
+    ADD COLUMN featured BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN featured_order INTEGER;
+
+CREATE INDEX posts_featured_order_idx ON posts (featured_order) WHERE featured;
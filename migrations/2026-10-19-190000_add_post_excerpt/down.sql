@@ -0,0 +1,2 @@
+ALTER TABLE posts DROP COLUMN IF EXISTS excerpt;
+DELETE FROM settings WHERE param = 'excerpt_length';
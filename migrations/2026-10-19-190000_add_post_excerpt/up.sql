@@ -0,0 +1,5 @@
+ALTER TABLE posts ADD COLUMN excerpt TEXT;
+
+INSERT INTO settings (param, value, note) VALUES
+    ('excerpt_length', '200', 'Maximum characters of the generated excerpt shown in post listings')
+ON CONFLICT (param) DO NOTHING;
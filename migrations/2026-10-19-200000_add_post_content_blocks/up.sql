@@ -0,0 +1 @@
+ALTER TABLE posts ADD COLUMN content_blocks JSONB;
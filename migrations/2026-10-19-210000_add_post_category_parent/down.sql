@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS post_categories_parent_id_idx;
+
+ALTER TABLE post_categories
+    DROP CONSTRAINT IF EXISTS post_categories_not_own_parent,
+    DROP COLUMN IF EXISTS parent_id;
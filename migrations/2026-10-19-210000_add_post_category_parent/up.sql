@@ -0,0 +1,5 @@
+ALTER TABLE post_categories
+    ADD COLUMN parent_id INTEGER REFERENCES post_categories(id) ON DELETE SET NULL,
+    ADD CONSTRAINT post_categories_not_own_parent CHECK (parent_id <> id);
+
+CREATE INDEX post_categories_parent_id_idx ON post_categories (parent_id);
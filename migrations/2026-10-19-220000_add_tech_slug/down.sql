@@ -0,0 +1 @@
+ALTER TABLE techs DROP COLUMN IF EXISTS slug;
@@ -0,0 +1,11 @@
+ALTER TABLE techs ADD COLUMN slug VARCHAR(255);
+
+UPDATE techs SET slug = trim(both '-' from regexp_replace(lower(title), '[^a-z0-9]+', '-', 'g'));
+UPDATE techs SET slug = 'tech' WHERE slug = '';
+-- Titles such as "C" and "C#" share a slug, later ones get their id appended
+UPDATE techs SET slug = slug || '-' || id
+WHERE id NOT IN (SELECT min(id) FROM techs GROUP BY slug);
+
+ALTER TABLE techs
+    ALTER COLUMN slug SET NOT NULL,
+    ADD CONSTRAINT techs_slug_key UNIQUE (slug);
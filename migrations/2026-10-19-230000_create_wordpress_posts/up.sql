@@ -0,0 +1,6 @@
+CREATE TABLE wordpress_posts (
+    guid TEXT PRIMARY KEY,
+    post_id INTEGER NOT NULL UNIQUE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    FOREIGN KEY (post_id) REFERENCES posts(id)
+);
@@ -0,0 +1,204 @@
+use serde::{Serialize, Deserialize};
+
+use crate::controller::image::is_uploaded_asset;
+use crate::controller::setting::SiteSettings;
+use crate::render::escape_xml;
+use crate::shortcode::{Embed, Embeds, parse_shortcodes};
+
+// Shortcode kinds an embed block may point at, images have their own block
+const EMBED_KINDS: [&str; 2] = ["project", "post"];
+
+// One block of structured post content as the block editor sends it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Block {
+    Paragraph {
+        text: String
+    },
+    Heading {
+        level: u8,
+        text: String
+    },
+    Code {
+        language: Option<String>,
+        code: String
+    },
+    Image {
+        src: String,
+        #[serde(default)]
+        alt: String,
+        caption: Option<String>
+    },
+    Quote {
+        text: String,
+        cite: Option<String>
+    },
+    Embed {
+        kind: String,
+        target: String
+    },
+    List {
+        #[serde(default)]
+        ordered: bool,
+        items: Vec<String>
+    }
+}
+
+impl Block {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Block::Paragraph { text } | Block::Quote { text, .. } if text.trim().is_empty() => Err("text can't be empty".to_string()),
+            Block::Heading { level, .. } if !(1..=6).contains(level) => Err("heading level must be between 1 and 6".to_string()),
+            Block::Heading { text, .. } if text.trim().is_empty() || text.contains('\n') => Err("heading text must be a single non empty line".to_string()),
+            Block::Code { language: Some(language), .. } if !language.chars().all(|c| c.is_ascii_alphanumeric() || "+-#._".contains(c)) => Err(format!("invalid code language {}", language)),
+            Block::Image { src, .. } if !is_uploaded_asset(src) && !((src.starts_with("https://") || src.starts_with("http://")) && !src.contains(char::is_whitespace)) => Err(format!("image {} must be an uploaded /assets image or an absolute URL", src)),
+            Block::Embed { kind, .. } if !EMBED_KINDS.contains(&kind.as_str()) => Err(format!("embed kind must be one of: {}", EMBED_KINDS.join(", "))),
+            Block::Embed { target, .. } if target.is_empty() || target.contains(char::is_whitespace) || target.contains('}') => Err(format!("invalid embed target {}", target)),
+            Block::List { items, .. } if items.is_empty() || items.iter().any(|item| item.trim().is_empty()) => Err("list items can't be empty".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        match self {
+            Block::Paragraph { text } => escape_markdown(text),
+            Block::Heading { level, text } => format!("{} {}", "#".repeat(*level as usize), escape_markdown(text)),
+            Block::Code { language, code } => {
+                // The fence has to outlast any backtick run inside the code
+                let longest_run = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+                let fence = "`".repeat(longest_run.max(2) + 1);
+                let newline = if code.ends_with('\n') { "" } else { "\n" };
+                format!("{}{}\n{}{}{}", fence, language.as_deref().unwrap_or(""), code, newline, fence)
+            }
+            Block::Image { src, alt, caption } => match caption {
+                Some(caption) => format!("![{}]({} \"{}\")", escape_markdown(alt), escape_destination(src), caption.replace('\\', "\\\\").replace('"', "\\\"")),
+                None => format!("![{}]({})", escape_markdown(alt), escape_destination(src)),
+            },
+            Block::Quote { text, cite } => {
+                let mut quote: Vec<String> = escape_markdown(text).lines().map(|line| format!("> {}", line).trim_end().to_string()).collect();
+                if let Some(cite) = cite {
+                    quote.push(">".to_string());
+                    quote.push(format!("> — {}", escape_markdown(cite)));
+                }
+                quote.join("\n")
+            }
+            Block::Embed { kind, target } => format!("{{{{{}:{}}}}}", kind, target),
+            Block::List { ordered, items } => items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    let marker = if *ordered { format!("{}.", index + 1) } else { "-".to_string() };
+                    // Continuation lines are indented so they stay inside the item
+                    let indent = " ".repeat(marker.len() + 1);
+                    format!("{} {}", marker, escape_markdown(item).lines().collect::<Vec<&str>>().join(&format!("\n{}", indent)))
+                })
+                .collect::<Vec<String>>()
+                .join("\n"),
+        }
+    }
+
+    pub fn to_html(&self, site: &SiteSettings, embeds: &Embeds) -> String {
+        match self {
+            Block::Paragraph { text } => text_paragraphs(text),
+            Block::Heading { level, text } => format!("<h{}>{}</h{}>", level, escape_xml(text), level),
+            Block::Code { language: Some(language), code } => format!("<pre><code class=\"language-{}\">{}</code></pre>", escape_xml(language), escape_xml(code)),
+            Block::Code { language: None, code } => format!("<pre><code>{}</code></pre>", escape_xml(code)),
+            Block::Image { src, alt, caption } => Embed::Image { src: src.clone(), alt: alt.clone(), caption: caption.clone() }.to_html(),
+            Block::Quote { text, cite } => match cite {
+                Some(cite) => format!("<blockquote>{}<p>— {}</p></blockquote>", text_paragraphs(text), escape_xml(cite)),
+                None => format!("<blockquote>{}</blockquote>", text_paragraphs(text)),
+            },
+            Block::Embed { .. } => {
+                let markdown = self.to_markdown();
+                match parse_shortcodes(&markdown).first().map(|shortcode| embeds.resolve(shortcode, site)) {
+                    Some(Ok(embed)) => embed.to_html(),
+                    _ => format!("<p>{}</p>", escape_xml(&markdown)),
+                }
+            }
+            Block::List { ordered, items } => {
+                let tag = if *ordered { "ol" } else { "ul" };
+                let items: String = items.iter().map(|item| format!("<li>{}</li>", escape_xml(item))).collect();
+                format!("<{}>{}</{}>", tag, items, tag)
+            }
+        }
+    }
+}
+
+// Backslash escapes what Markdown would read as formatting, block text is shown as typed
+fn escape_markdown(text: &str) -> String {
+    text.lines().map(escape_markdown_line).collect::<Vec<String>>().join("\n")
+}
+
+fn escape_markdown_line(line: &str) -> String {
+    let content = line.trim_start_matches([' ', '\t']);
+    // Leading whitespace would otherwise turn into an indented code block or get dropped
+    let mut escaped: String = line[..line.len() - content.len()]
+        .chars()
+        .map(|c| if c == '\t' { "&#9;" } else { "&#32;" })
+        .collect();
+
+    let ordered_marker = content.find(|c: char| !c.is_ascii_digit()).filter(|&end| end > 0 && content[end..].starts_with(['.', ')']));
+    for (index, c) in content.char_indices() {
+        let starts_block = index == 0 && "-+=".contains(c);
+        if starts_block || ordered_marker == Some(index) || "\\`*_{}[]<>#|~&".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+// Link destinations end at a space or an unbalanced parenthesis
+fn escape_destination(src: &str) -> String {
+    let mut escaped = String::with_capacity(src.len());
+    for c in src.chars() {
+        match c {
+            ' ' => escaped.push_str("%20"),
+            '\\' | '(' | ')' | '<' | '>' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Blank lines split block text into paragraphs, the way its Markdown renders
+fn text_paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .filter(|paragraph| !paragraph.trim().is_empty())
+        .map(|paragraph| format!("<p>{}</p>", escape_xml(paragraph)))
+        .collect()
+}
+
+pub fn validate_blocks(blocks: &[Block]) -> Result<(), String> {
+    if blocks.is_empty() {
+        return Err("Content blocks can't be empty".to_string());
+    }
+
+    for (index, block) in blocks.iter().enumerate() {
+        block.validate().map_err(|e| format!("Content block {}: {}", index + 1, e))?;
+    }
+
+    Ok(())
+}
+
+// The Markdown equivalent stored in `posts.content`, so listings, feeds, search and exports keep working
+pub fn blocks_to_markdown(blocks: &[Block]) -> String {
+    blocks
+        .iter()
+        .map(Block::to_markdown)
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+// Rendered straight from the blocks, so their text never passes through a Markdown parser
+pub fn blocks_to_html(blocks: &[Block], site: &SiteSettings, embeds: &Embeds) -> String {
+    blocks
+        .iter()
+        .map(|block| block.to_html(site, embeds))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
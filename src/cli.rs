@@ -0,0 +1,80 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::DBPool;
+use crate::constants::CONNECTION_POOL_ERROR;
+use crate::importer::{ImportOptions, import_markdown_zip};
+use crate::wxr::{WxrOptions, import_wxr};
+
+const USAGE: &str = "Usage:
+  sedikitacakv3-api import-markdown <posts.zip> --author <user uuid> [--dry-run]
+  sedikitacakv3-api import-wxr <export.xml> --attachments <directory> [--author <fallback user uuid>] [--dry-run]";
+
+// Value following `--name` in the arguments
+fn option_value(args: &[String], name: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, format!("{}\n{}", message, USAGE))
+}
+
+fn import_markdown(args: &[String], pool: &DBPool) -> io::Result<()> {
+    let zip_path = args.first()
+        .filter(|zip_path| !zip_path.starts_with("--"))
+        .ok_or(invalid("Missing zip file".to_string()))?;
+    let author_id = option_value(args, "--author")
+        .ok_or(invalid("Missing --author".to_string()))
+        .and_then(|author_id| Uuid::parse_str(&author_id).map_err(|e| invalid(e.to_string())))?;
+    let options = ImportOptions {
+        author_id,
+        dry_run: args.iter().any(|arg| arg == "--dry-run"),
+    };
+
+    let bytes = fs::read(zip_path)?;
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    let report = import_markdown_zip(bytes, &options, &mut conn)
+        .map_err(|e| io::Error::other(e.to_string()))?;
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+fn import_wordpress(args: &[String], pool: &DBPool) -> io::Result<()> {
+    let xml_path = args.first()
+        .filter(|xml_path| !xml_path.starts_with("--"))
+        .ok_or(invalid("Missing WXR file".to_string()))?;
+    let attachments_dir = option_value(args, "--attachments")
+        .ok_or(invalid("Missing --attachments".to_string()))?;
+    let fallback_author_id = match option_value(args, "--author") {
+        Some(author_id) => Some(Uuid::parse_str(&author_id).map_err(|e| invalid(e.to_string()))?),
+        None => None,
+    };
+    let options = WxrOptions {
+        attachments_dir: PathBuf::from(attachments_dir),
+        fallback_author_id,
+        dry_run: args.iter().any(|arg| arg == "--dry-run"),
+    };
+
+    let xml = fs::read_to_string(xml_path)?;
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    let report = import_wxr(&xml, &options, &mut conn)
+        .map_err(|e| io::Error::other(e.to_string()))?;
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+// Runs a maintenance command instead of the web server
+pub fn run(args: &[String], pool: &DBPool) -> io::Result<()> {
+    match args.first().map(String::as_str) {
+        Some("import-markdown") => import_markdown(&args[1..], pool),
+        Some("import-wxr") => import_wordpress(&args[1..], pool),
+        _ => Err(invalid(format!("Unknown command {}", args.join(" ")))),
+    }
+}
@@ -1,5 +1,28 @@
 pub const APPLICATION_JSON: &str = "application/json";
 
+pub const APPLICATION_XML: &str = "application/xml; charset=utf-8";
+
+pub const APPLICATION_RSS: &str = "application/rss+xml; charset=utf-8";
+
+pub const APPLICATION_ATOM: &str = "application/atom+xml; charset=utf-8";
+
+pub const APPLICATION_FEED_JSON: &str = "application/feed+json; charset=utf-8";
+
+pub const APPLICATION_ZIP: &str = "application/zip";
+
+// Upper bound for the `limit` of public list endpoints
+pub const MAX_PAGE_LIMIT: i32 = 100;
+
 pub const CONNECTION_POOL_ERROR: &str = "couldn't get DB connection from pool";
 
-pub const USER_BIRTH_NOTFOUND: &str = "couldn't get user birth value";
\ No newline at end of file
+pub const USER_BIRTH_NOTFOUND: &str = "couldn't get user birth value";
+
+// Largest Markdown zip the import endpoint accepts and largest file unpacked from it
+pub const MAX_IMPORT_SIZE: usize = 50 * 1024 * 1024;
+
+pub const MAX_IMPORT_ENTRY_SIZE: u64 = 20 * 1024 * 1024;
+
+// Transaction level advisory lock keys serializing changes to the display order
+pub const PROJECT_ORDER_LOCK: i64 = 4_201;
+
+pub const HOBBY_ORDER_LOCK: i64 = 4_202;
@@ -0,0 +1,385 @@
+use actix_web::{post, get, delete, web, HttpRequest, HttpResponse};
+use chrono::{Utc, NaiveDateTime, DateTime, TimeZone};
+use serde::{Serialize, Deserialize};
+use diesel::result::Error;
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, PgTextExpressionMethods, BoolExpressionMethods};
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use log::error;
+use std::env;
+
+use crate::errors::NotifyError;
+
+use crate::constants::{APPLICATION_JSON, CONNECTION_POOL_ERROR};
+use crate::{DBPool, DBPooledConnection};
+
+use crate::controller::setting::get_setting_value;
+use crate::models::CommentDB;
+
+const COMMENT_STATUSES: [&str; 3] = ["pending", "approved", "spam"];
+
+// Comment Request Struct
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CommentRequest {
+    pub name: String,
+    pub email: String,
+    pub content: String,
+    pub parent_id: Option<i32>
+}
+
+impl CommentRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() || self.content.trim().is_empty() {
+            return Err("Name and comment cannot be empty".to_string());
+        }
+
+        if !self.email.contains('@') {
+            return Err("Invalid email address".to_string());
+        }
+
+        if self.name.chars().count() > 255 || self.email.chars().count() > 255 {
+            return Err("Name and email can't be longer than 255 characters".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommentStatusRequest {
+    pub status: String
+}
+
+#[derive(Debug, Serialize)]
+pub struct PublicComment {
+    pub id: i32,
+    pub parent_id: Option<i32>,
+    pub name: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub replies: Vec<PublicComment>
+}
+
+// Pagination Request Struct
+#[derive(Debug, Deserialize)]
+pub struct PaginationParams {
+    pub page: Option<i32>,
+    pub limit: Option<i32>,
+    pub status: Option<String>,
+    pub post_id: Option<i32>,
+    pub search: Option<String>
+}
+
+// Class Wide Function
+
+fn is_valid_status(status: &str) -> bool {
+    COMMENT_STATUSES.contains(&status)
+}
+
+pub fn get_published_post_id(post_slug: &str, conn: &mut DBPooledConnection) -> Result<i32, Error> {
+    use crate::schema::posts::dsl::*;
+
+    posts
+        .filter(slug.eq(post_slug))
+        .filter(published.eq(true))
+        .filter(deleted_at.is_null())
+        .select(id)
+        .first::<i32>(conn)
+}
+
+fn create_comment(comment_post_id: i32, comment: CommentRequest, ip: String, conn: &mut DBPooledConnection) -> Result<CommentDB, Error> {
+    use crate::schema::comments::dsl::*;
+
+    if let Some(comment_parent_id) = comment.parent_id {
+        // Replies can only hang off approved comments of the same post
+        comments
+            .filter(id.eq(comment_parent_id))
+            .filter(post_id.eq(comment_post_id))
+            .filter(status.eq("approved"))
+            .filter(deleted_at.is_null())
+            .select(id)
+            .first::<i32>(conn)?;
+    }
+
+    let default_status = get_setting_value("comment_default_status", conn)
+        .filter(|setting_status| is_valid_status(setting_status))
+        .unwrap_or("pending".to_string());
+
+    diesel::insert_into(comments)
+        .values((
+            post_id.eq(comment_post_id),
+            parent_id.eq(comment.parent_id),
+            name.eq(comment.name),
+            email.eq(comment.email),
+            content.eq(comment.content),
+            status.eq(default_status),
+            ip_address.eq(Some(ip)),
+            created_at.eq(Utc::now().naive_utc()),
+            updated_at.eq(Utc::now().naive_utc()),
+        ))
+        .get_result(conn)
+}
+
+fn notify_author(comment: &CommentDB, conn: &mut DBPooledConnection) -> Result<(), NotifyError> {
+    use crate::schema::posts::dsl::{posts, id as post_id, title as post_title};
+    use crate::schema::users::dsl::{users, name as user_name, email as user_email};
+
+    let (title, author_name, author_email) = posts
+        .inner_join(users)
+        .filter(post_id.eq(comment.post_id))
+        .select((post_title, user_name, user_email))
+        .first::<(String, String, String)>(conn)?;
+
+    let emsg = Message::builder()
+        .from("Sedikit Acak CF <cf@sedikitacak.com>".parse()?)
+        .reply_to(format!("{} <{}>", comment.name, comment.email).parse()?)
+        .to(format!("{} <{}>", author_name, author_email).parse()?)
+        .subject(format!("New comment on \"{}\" ({})", title, comment.status))
+        .header(ContentType::TEXT_PLAIN)
+        .body(format!("{} wrote:\n\n{}", comment.name, comment.content))?;
+
+    let user = env::var("SMTP_USER")?;
+    let pass = env::var("SMTP_PASS")?;
+    let creds = Credentials::new(user, pass);
+
+    let mailer = SmtpTransport::relay("smtp.gmail.com")?
+        .credentials(creds)
+        .build();
+
+    mailer.send(&emsg)?;
+
+    Ok(())
+}
+
+fn build_thread(parent: Option<i32>, approved: &[CommentDB]) -> Vec<PublicComment> {
+    approved
+        .iter()
+        .filter(|comment| comment.parent_id == parent)
+        .map(|comment| PublicComment {
+            id: comment.id,
+            parent_id: comment.parent_id,
+            name: comment.name.clone(),
+            content: comment.content.clone(),
+            created_at: Utc.from_utc_datetime(&comment.created_at),
+            replies: build_thread(Some(comment.id), approved),
+        })
+        .collect()
+}
+
+fn approved_comments(comment_post_id: i32, conn: &mut DBPooledConnection) -> Result<Vec<PublicComment>, Error> {
+    use crate::schema::comments::dsl::*;
+
+    let approved = comments
+        .filter(post_id.eq(comment_post_id))
+        .filter(status.eq("approved"))
+        .filter(deleted_at.is_null())
+        .order_by(created_at.asc())
+        .load::<CommentDB>(conn)?;
+
+    Ok(build_thread(None, &approved))
+}
+
+fn all_comment_with_pagination(page: i32, limit: i32, comment_status: String, comment_post_id: Option<i32>, search: String, conn: &mut DBPooledConnection) -> Result<Vec<CommentDB>, Error> {
+    use crate::schema::comments::dsl::*;
+
+    let mut query = comments
+        .filter(deleted_at.is_null())
+        .order_by(id.desc())
+        .limit(limit as i64)
+        .offset(((page - 1) * limit) as i64)
+        .into_boxed();
+
+    if !comment_status.is_empty() {
+        query = query.filter(status.eq(comment_status));
+    }
+
+    if let Some(comment_post_id) = comment_post_id {
+        query = query.filter(post_id.eq(comment_post_id));
+    }
+
+    if !search.is_empty() {
+        query = query.filter(
+            name.ilike(format!("%{}%", search))
+                .or(email.ilike(format!("%{}%", search)))
+                .or(content.ilike(format!("%{}%", search)))
+        );
+    }
+
+    query.load::<CommentDB>(conn)
+}
+
+// Routing
+
+#[post("/post/{slug}/comment")]
+pub async fn submit(path: web::Path<String>, comment_req: web::Json<CommentRequest>, http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
+    let post_slug = path.into_inner();
+    let comment_req = comment_req.into_inner();
+
+    if let Err(e) = comment_req.validate() {
+        return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": e}));
+    }
+
+    let ip = http_req.connection_info().realip_remote_addr().unwrap_or("").to_string();
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    let comment_post_id = match get_published_post_id(&post_slug, &mut conn) {
+        Ok(comment_post_id) => comment_post_id,
+        Err(_) => return HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Post not found"})),
+    };
+
+    match create_comment(comment_post_id, comment_req, ip, &mut conn) {
+        Ok(inserted_comment) => {
+            if let Err(e) = notify_author(&inserted_comment, &mut conn) {
+                error!("Comment notification failed: {:?}", e);
+            }
+
+            HttpResponse::Created()
+                .content_type(APPLICATION_JSON)
+                .json(serde_json::json!({
+                    "message": "Comment submitted",
+                    "status": inserted_comment.status
+                }))
+        }
+        Err(Error::NotFound) => HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Replied comment not found"})),
+        Err(e) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error inserting comment: {}", e)),
+    }
+}
+
+#[get("/post/{slug}/comments")]
+pub async fn thread(path: web::Path<String>, pool: web::Data<DBPool>) -> HttpResponse {
+    let post_slug = path.into_inner();
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    let comment_post_id = match get_published_post_id(&post_slug, &mut conn) {
+        Ok(comment_post_id) => comment_post_id,
+        Err(_) => return HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Post not found"})),
+    };
+
+    match approved_comments(comment_post_id, &mut conn) {
+        Ok(comment_thread) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(comment_thread),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve comments"})),
+    }
+}
+
+#[get("/comment/{id}")]
+pub async fn get(path: web::Path<i32>, pool: web::Data<DBPool>) -> HttpResponse {
+    let comment_id = path.into_inner();
+
+    use crate::schema::comments::dsl::*;
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match comments.filter(id.eq(comment_id)).first::<CommentDB>(&mut conn) {
+        Ok(comment) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(comment),
+        Err(_) => HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json("Comment not found"),
+    }
+}
+
+#[post("/comment/{id}/status")]
+pub async fn moderate(path: web::Path<i32>, status_req: web::Json<CommentStatusRequest>, pool: web::Data<DBPool>) -> HttpResponse {
+    let comment_id = path.into_inner();
+
+    if !is_valid_status(&status_req.status) {
+        return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Status must be one of pending, approved or spam"}));
+    }
+
+    use crate::schema::comments::dsl::*;
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match diesel::update(comments.filter(id.eq(comment_id)))
+        .set((
+            status.eq(&status_req.status),
+            updated_at.eq(Utc::now().naive_utc()),
+        ))
+        .get_result::<CommentDB>(&mut conn)
+    {
+        Ok(updated_comment) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(updated_comment),
+        Err(Error::NotFound) => HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Comment not found"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to moderate comment"})),
+    }
+}
+
+#[delete("/comment/{id}")]
+pub async fn delete(path: web::Path<i32>, pool: web::Data<DBPool>) -> HttpResponse {
+    let comment_id = path.into_inner();
+    let current_time = Utc::now().naive_utc();
+
+    use crate::schema::comments::dsl::*;
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match diesel::update(comments.filter(id.eq(comment_id)))
+        .set(deleted_at.eq(Some(current_time)))
+        .execute(&mut conn)
+    {
+        Ok(_) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Comment successfully deleted"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to delete comment"})),
+    }
+}
+
+#[post("/comment/{id}/restore")]
+pub async fn restore(path: web::Path<i32>, pool: web::Data<DBPool>) -> HttpResponse {
+    let comment_id = path.into_inner();
+
+    use crate::schema::comments::dsl::*;
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match diesel::update(comments.filter(id.eq(comment_id)))
+        .set(deleted_at.eq(None::<NaiveDateTime>))
+        .execute(&mut conn)
+    {
+        Ok(_) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Comment successfully restored"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to restore comment"})),
+    }
+}
+
+#[get("/comments")]
+pub async fn all(query: web::Query<PaginationParams>, pool: web::Data<DBPool>) -> HttpResponse {
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(20);
+    let status = query.status.clone().unwrap_or("".to_string());
+    let search = query.search.clone().unwrap_or("".to_string());
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match all_comment_with_pagination(page, limit, status, query.post_id, search, &mut conn) {
+        Ok(comments) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(comments),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve comments"})),
+    }
+}
@@ -0,0 +1,189 @@
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_files::NamedFile;
+use chrono::{NaiveDate, NaiveDateTime, TimeZone, Utc, Days};
+use serde::{Serialize, Deserialize};
+use diesel::result::Error;
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, SelectableHelper};
+use zip::write::{FileOptions, ZipWriter};
+use zip::CompressionMethod;
+use log::error;
+use uuid::Uuid;
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, Seek, Write};
+use std::path::Path;
+
+use crate::constants::{APPLICATION_JSON, APPLICATION_ZIP, CONNECTION_POOL_ERROR};
+use crate::{DBPool, DBPooledConnection};
+use crate::errors::ExportError;
+
+use crate::importer::rewrite_images;
+use crate::models::PostDB;
+
+// Export Request Struct
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    pub cat: Option<String>,
+    pub published: Option<bool>,
+    pub from: Option<NaiveDate>,
+    pub until: Option<NaiveDate>
+}
+
+// Front matter written on top of every exported post, readable by the Markdown import
+#[derive(Debug, Serialize)]
+struct ExportFrontMatter {
+    title: String,
+    subtitle: Option<String>,
+    slug: String,
+    date: String,
+    updated: String,
+    category: String,
+    tags: Vec<String>,
+    published: bool,
+    author: String,
+    author_id: Uuid
+}
+
+// Class Wide Function
+
+fn export_posts(params: &ExportParams, conn: &mut DBPooledConnection) -> Result<Vec<(PostDB, String, String)>, Error> {
+    use crate::schema::posts::dsl::*;
+    use crate::schema::post_categories::dsl::{post_categories, slug as category_slug};
+    use crate::schema::users::dsl::{users, name as author_name};
+
+    let mut query = posts
+        .inner_join(post_categories)
+        .inner_join(users)
+        .filter(deleted_at.is_null())
+        .order_by(created_at.asc())
+        .select((PostDB::as_select(), category_slug, author_name))
+        .into_boxed();
+
+    if let Some(cat) = &params.cat {
+        query = query.filter(category_slug.eq(cat.clone()));
+    }
+
+    if let Some(is_published) = params.published {
+        query = query.filter(published.eq(is_published));
+    }
+
+    if let Some(from) = params.from.and_then(|from| from.and_hms_opt(0, 0, 0)) {
+        query = query.filter(created_at.ge(from));
+    }
+
+    // `until` is inclusive, everything before the next midnight belongs to it
+    if let Some(until) = params.until.and_then(|until| until.checked_add_days(Days::new(1))).and_then(|until| until.and_hms_opt(0, 0, 0)) {
+        query = query.filter(created_at.lt(until));
+    }
+
+    query.load::<(PostDB, String, String)>(conn)
+}
+
+fn rfc3339(datetime: &NaiveDateTime) -> String {
+    Utc.from_utc_datetime(datetime).to_rfc3339()
+}
+
+fn markdown_document(post: &PostDB, category: String, author: String, content: &str) -> Result<String, serde_yaml::Error> {
+    let front_matter = ExportFrontMatter {
+        title: post.title.clone(),
+        subtitle: post.subtitle.clone().filter(|subtitle| !subtitle.is_empty()),
+        slug: post.slug.clone(),
+        date: rfc3339(&post.created_at),
+        updated: rfc3339(&post.updated_at),
+        category,
+        tags: post.tags
+            .as_deref()
+            .unwrap_or("")
+            .split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect(),
+        published: post.published,
+        author,
+        author_id: post.author_id,
+    };
+
+    Ok(format!("---\n{}---\n\n{}\n", serde_yaml::to_string(&front_matter)?, content))
+}
+
+// Posts land in posts/{slug}.md, the uploads they show in uploads/ with links made relative to match.
+// The archive is written to an unnamed temporary file that goes away once the response is sent
+fn build_archive(exported: Vec<(PostDB, String, String)>) -> Result<File, ExportError> {
+    let mut zip = ZipWriter::new(tempfile::tempfile()?);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut uploads: BTreeSet<String> = BTreeSet::new();
+
+    for (post, category, author) in exported {
+        let content = rewrite_images(&post.content, |url| {
+            let filename = url.strip_prefix("/assets/")?;
+            if filename.contains("..") || !Path::new("./uploads").join(filename).is_file() {
+                return None;
+            }
+            uploads.insert(filename.to_string());
+            Some(format!("../uploads/{}", filename))
+        });
+
+        zip.start_file(format!("posts/{}.md", post.slug), options)?;
+        zip.write_all(markdown_document(&post, category, author, &content)?.as_bytes())?;
+    }
+
+    for filename in uploads {
+        zip.start_file(format!("uploads/{}", filename), options)?;
+        io::copy(&mut File::open(Path::new("./uploads").join(&filename))?, &mut zip)?;
+    }
+
+    let mut archive = zip.finish()?;
+    archive.rewind()?;
+    Ok(archive)
+}
+
+// Runs on the blocking thread pool, the query and the archive both block
+fn export_archive(params: ExportParams, pool: DBPool) -> Result<File, ExportError> {
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    let exported = export_posts(&params, &mut conn)?;
+    drop(conn);
+
+    build_archive(exported)
+}
+
+// Routing
+
+#[get("/posts/export")]
+pub async fn posts(http_req: HttpRequest, query: web::Query<ExportParams>, pool: web::Data<DBPool>) -> HttpResponse {
+    let params = query.into_inner();
+    let pool = pool.get_ref().clone();
+
+    let archive = match web::block(move || export_archive(params, pool)).await {
+        Ok(Ok(archive)) => archive,
+        Ok(Err(ExportError::Database(_))) => return HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve posts"})),
+        result => {
+            error!("Post export failed: {:?}", result);
+            return HttpResponse::InternalServerError()
+                .content_type(APPLICATION_JSON)
+                .json(serde_json::json!({"message": "Failed to build export"}));
+        }
+    };
+
+    let filename = format!("posts-{}.zip", Utc::now().format("%Y%m%d"));
+    match NamedFile::from_file(archive, &filename) {
+        Ok(archive) => archive
+            .set_content_type(APPLICATION_ZIP.parse().expect("valid zip mime type"))
+            .set_content_disposition(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(filename)],
+            })
+            .use_last_modified(false)
+            .use_etag(false)
+            .into_response(&http_req),
+        Err(e) => {
+            error!("Post export failed: {:?}", e);
+            HttpResponse::InternalServerError()
+                .content_type(APPLICATION_JSON)
+                .json(serde_json::json!({"message": "Failed to build export"}))
+        }
+    }
+}
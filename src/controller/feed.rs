@@ -0,0 +1,284 @@
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use actix_web::http::header::{Header, ETag, EntityTag, HttpDate, IfModifiedSince, IfNoneMatch, LastModified};
+use chrono::{Utc, NaiveDateTime, DateTime, TimeZone};
+use serde::Serialize;
+use diesel::result::Error;
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods};
+use sha2::{Sha256, Digest};
+use std::time::SystemTime;
+
+use crate::constants::{APPLICATION_JSON, APPLICATION_RSS, APPLICATION_ATOM, APPLICATION_FEED_JSON, CONNECTION_POOL_ERROR};
+use crate::{DBPool, DBPooledConnection};
+
+use crate::controller::post::{JoinedPost, PostFilter, all_post_with_pagination, render_posts_html};
+use crate::controller::setting::SiteSettings;
+use crate::models::PostCatDB;
+use crate::render::escape_xml;
+
+// Feed Struct
+pub struct FeedSource {
+    pub site: SiteSettings,
+    pub title: String,
+    pub self_url: String,
+    pub posts: Vec<JoinedPost>
+}
+
+impl FeedSource {
+    pub fn last_modified(&self) -> Option<NaiveDateTime> {
+        self.posts.iter().map(|joined| joined.post.updated_at).max()
+    }
+}
+
+// JSON Feed 1.1 Struct
+#[derive(Debug, Serialize)]
+pub struct JsonFeed {
+    pub version: String,
+    pub title: String,
+    pub home_page_url: String,
+    pub feed_url: String,
+    pub description: String,
+    pub items: Vec<JsonFeedItem>
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonFeedItem {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub content_html: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    pub date_published: String,
+    pub date_modified: String,
+    pub authors: Vec<JsonFeedAuthor>,
+    pub tags: Vec<String>
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonFeedAuthor {
+    pub name: String
+}
+
+// Class Wide Function
+
+fn get_published_category(cat_slug: &str, conn: &mut DBPooledConnection) -> Result<PostCatDB, Error> {
+    use crate::schema::post_categories::dsl::*;
+
+    post_categories
+        .filter(slug.eq(cat_slug))
+        .filter(published.eq(true))
+        .filter(deleted_at.is_null())
+        .first::<PostCatDB>(conn)
+}
+
+pub fn load_feed(cat_slug: Option<String>, self_url: String, conn: &mut DBPooledConnection) -> Result<FeedSource, Error> {
+    let site = SiteSettings::load(conn);
+
+    let title = match &cat_slug {
+        Some(cat_slug) => format!("{} - {}", site.title, get_published_category(cat_slug, conn)?.name),
+        None => site.title.clone(),
+    };
+
+    let filter = PostFilter {
+        cat: cat_slug.unwrap_or_default(),
+        is_published: true,
+        ..Default::default()
+    };
+    let mut posts = all_post_with_pagination(1, site.feed_size, filter, conn)?;
+    render_posts_html(&mut posts, &site, conn)?;
+
+    Ok(FeedSource {
+        site,
+        title,
+        self_url,
+        posts
+    })
+}
+
+fn to_utc(datetime: &NaiveDateTime) -> DateTime<Utc> {
+    Utc.from_utc_datetime(datetime)
+}
+
+fn rss_document(feed: &FeedSource) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\" xmlns:content=\"http://purl.org/rss/1.0/modules/content/\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n");
+    xml.push_str("<channel>\n");
+    xml.push_str(&format!("<title>{}</title>\n", escape_xml(&feed.title)));
+    xml.push_str(&format!("<link>{}</link>\n", escape_xml(&feed.site.url)));
+    xml.push_str(&format!("<description>{}</description>\n", escape_xml(&feed.site.description)));
+    xml.push_str(&format!("<atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>\n", escape_xml(&feed.self_url)));
+    if let Some(last_modified) = feed.last_modified() {
+        xml.push_str(&format!("<lastBuildDate>{}</lastBuildDate>\n", to_utc(&last_modified).to_rfc2822()));
+    }
+
+    for joined in &feed.posts {
+        let link = feed.site.post_url(&joined.post.slug);
+        xml.push_str("<item>\n");
+        xml.push_str(&format!("<title>{}</title>\n", escape_xml(&joined.post.title)));
+        xml.push_str(&format!("<link>{}</link>\n", escape_xml(&link)));
+        xml.push_str(&format!("<guid isPermaLink=\"true\">{}</guid>\n", escape_xml(&link)));
+        xml.push_str(&format!("<description>{}</description>\n", escape_xml(joined.post.subtitle.as_deref().unwrap_or(""))));
+        xml.push_str(&format!("<content:encoded>{}</content:encoded>\n", escape_xml(joined.content_html.as_deref().unwrap_or_default())));
+        for author_name in joined.author_names() {
+            xml.push_str(&format!("<dc:creator>{}</dc:creator>\n", escape_xml(&author_name)));
+        }
+        xml.push_str(&format!("<category>{}</category>\n", escape_xml(&joined.category.name)));
+        xml.push_str(&format!("<pubDate>{}</pubDate>\n", to_utc(&joined.post.created_at).to_rfc2822()));
+        xml.push_str("</item>\n");
+    }
+
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+fn atom_document(feed: &FeedSource) -> String {
+    let updated = feed.last_modified().unwrap_or_else(|| Utc::now().naive_utc());
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("<id>{}</id>\n", escape_xml(&feed.self_url)));
+    xml.push_str(&format!("<title>{}</title>\n", escape_xml(&feed.title)));
+    xml.push_str(&format!("<subtitle>{}</subtitle>\n", escape_xml(&feed.site.description)));
+    xml.push_str(&format!("<updated>{}</updated>\n", to_utc(&updated).to_rfc3339()));
+    xml.push_str(&format!("<link rel=\"alternate\" href=\"{}\"/>\n", escape_xml(&feed.site.url)));
+    xml.push_str(&format!("<link rel=\"self\" href=\"{}\"/>\n", escape_xml(&feed.self_url)));
+
+    for joined in &feed.posts {
+        let link = feed.site.post_url(&joined.post.slug);
+        xml.push_str("<entry>\n");
+        xml.push_str(&format!("<id>{}</id>\n", escape_xml(&link)));
+        xml.push_str(&format!("<title>{}</title>\n", escape_xml(&joined.post.title)));
+        xml.push_str(&format!("<link rel=\"alternate\" href=\"{}\"/>\n", escape_xml(&link)));
+        xml.push_str(&format!("<published>{}</published>\n", to_utc(&joined.post.created_at).to_rfc3339()));
+        xml.push_str(&format!("<updated>{}</updated>\n", to_utc(&joined.post.updated_at).to_rfc3339()));
+        for author_name in joined.author_names() {
+            xml.push_str(&format!("<author><name>{}</name></author>\n", escape_xml(&author_name)));
+        }
+        xml.push_str(&format!("<category term=\"{}\" label=\"{}\"/>\n", escape_xml(&joined.category.slug), escape_xml(&joined.category.name)));
+        xml.push_str(&format!("<summary>{}</summary>\n", escape_xml(joined.post.subtitle.as_deref().unwrap_or(""))));
+        xml.push_str(&format!("<content type=\"html\">{}</content>\n", escape_xml(joined.content_html.as_deref().unwrap_or_default())));
+        xml.push_str("</entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn json_feed_document(feed: &FeedSource) -> String {
+    let items = feed.posts
+        .iter()
+        .map(|joined| {
+            let link = feed.site.post_url(&joined.post.slug);
+            let tags = joined.post.tags
+                .as_deref()
+                .unwrap_or("")
+                .split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+
+            JsonFeedItem {
+                id: link.clone(),
+                url: link,
+                title: joined.post.title.clone(),
+                content_html: joined.content_html.clone().unwrap_or_default(),
+                summary: joined.post.subtitle.clone().filter(|subtitle| !subtitle.is_empty()),
+                image: joined.post.cover_image.as_deref().map(|cover_image| feed.site.absolute_url(cover_image)),
+                date_published: to_utc(&joined.post.created_at).to_rfc3339(),
+                date_modified: to_utc(&joined.post.updated_at).to_rfc3339(),
+                authors: joined.author_names().into_iter().map(|name| JsonFeedAuthor { name }).collect(),
+                tags,
+            }
+        })
+        .collect();
+
+    let document = JsonFeed {
+        version: "https://jsonfeed.org/version/1.1".to_string(),
+        title: feed.title.clone(),
+        home_page_url: feed.site.url.clone(),
+        feed_url: feed.self_url.clone(),
+        description: feed.site.description.clone(),
+        items,
+    };
+
+    serde_json::to_string(&document).unwrap_or_default()
+}
+
+// Answers with 304 when the client already holds the same document
+pub fn conditional_response(http_req: &HttpRequest, body: String, content_type: &str, last_modified: Option<NaiveDateTime>) -> HttpResponse {
+    let etag = EntityTag::new_strong(hex::encode(Sha256::digest(body.as_bytes())));
+    let last_modified = last_modified.map(|modified| SystemTime::from(to_utc(&modified)));
+
+    let not_modified = match IfNoneMatch::parse(http_req) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&etag)),
+        Err(_) => match (IfModifiedSince::parse(http_req), last_modified) {
+            (Ok(IfModifiedSince(since)), Some(modified)) => {
+                let since = DateTime::<Utc>::from(SystemTime::from(since)).timestamp();
+                DateTime::<Utc>::from(modified).timestamp() <= since
+            }
+            _ => false,
+        },
+    };
+
+    let mut response = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+
+    response.insert_header(ETag(etag));
+    if let Some(modified) = last_modified {
+        response.insert_header(LastModified(HttpDate::from(modified)));
+    }
+
+    if not_modified {
+        response.finish()
+    } else {
+        response.content_type(content_type).body(body)
+    }
+}
+
+fn feed_response(http_req: HttpRequest, cat_slug: Option<String>, pool: web::Data<DBPool>, content_type: &str, render: fn(&FeedSource) -> String) -> HttpResponse {
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match load_feed(cat_slug, http_req.full_url().to_string(), &mut conn) {
+        Ok(feed) => conditional_response(&http_req, render(&feed), content_type, feed.last_modified()),
+        Err(Error::NotFound) => HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Post category not found"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to build feed"})),
+    }
+}
+
+// Routing
+
+#[get("/feed.xml")]
+pub async fn rss(http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
+    feed_response(http_req, None, pool, APPLICATION_RSS, rss_document)
+}
+
+#[get("/atom.xml")]
+pub async fn atom(http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
+    feed_response(http_req, None, pool, APPLICATION_ATOM, atom_document)
+}
+
+#[get("/feed.json")]
+pub async fn json(http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
+    feed_response(http_req, None, pool, APPLICATION_FEED_JSON, json_feed_document)
+}
+
+#[get("/category/{slug}/feed.xml")]
+pub async fn category_rss(path: web::Path<String>, http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
+    feed_response(http_req, Some(path.into_inner()), pool, APPLICATION_RSS, rss_document)
+}
+
+#[get("/category/{slug}/atom.xml")]
+pub async fn category_atom(path: web::Path<String>, http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
+    feed_response(http_req, Some(path.into_inner()), pool, APPLICATION_ATOM, atom_document)
+}
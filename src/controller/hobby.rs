@@ -2,10 +2,12 @@ use actix_web::{post, get, delete, web, HttpResponse};
 use chrono::{Utc, NaiveDateTime};
 use serde::{Serialize, Deserialize};
 use diesel::result::Error;
-use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, PgTextExpressionMethods, BoolExpressionMethods};
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, PgTextExpressionMethods, BoolExpressionMethods, Connection};
 
 use crate::constants::{APPLICATION_JSON, CONNECTION_POOL_ERROR};
 use crate::{DBPool, DBPooledConnection};
+use crate::errors::ReorderError;
+use crate::ordering::{self, Ordered};
 
 use crate::models::HobbyDB;
 
@@ -17,8 +19,7 @@ pub struct HobbyRequest {
     pub image: Option<String>,
     pub item_order: i32,
     pub active: bool,
-    pub published: bool,
-    pub order: i32
+    pub published: bool
 }
 
 impl HobbyRequest {
@@ -34,11 +35,17 @@ impl HobbyRequest {
             updated_at: Utc::now().naive_utc(),
             deleted_at: None,
             published: self.published,
-            order: self.order.clone(),
+            // Orders are handed out on create and only change through /hobbies/reorder
+            order: 0,
         })
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ReorderRequest {
+    pub ids: Vec<i32>
+}
+
 // Pagination Request Struct
 #[derive(Debug, Deserialize)]
 pub struct PaginationParams {
@@ -51,21 +58,24 @@ pub struct PaginationParams {
 
 fn create_hobby(hobby: HobbyDB, conn: &mut DBPooledConnection) -> Result<HobbyDB, Error> {
     use crate::schema::hobbies::dsl::*;
-    let next_order =  get_next_order(conn)?;
-    diesel::insert_into(hobbies)
-        .values((
-            title.eq(hobby.title),
-            content.eq(hobby.content),
-            image.eq(hobby.image),
-            item_order.eq(hobby.item_order),
-            active.eq(hobby.active),
-            created_at.eq(hobby.created_at),
-            updated_at.eq(hobby.updated_at),
-            deleted_at.eq(hobby.deleted_at),
-            published.eq(hobby.published),
-            order.eq(next_order)
-        ))
-        .get_result(conn)
+    conn.transaction(|conn| {
+        ordering::lock_ordering(Ordered::Hobbies, conn)?;
+        let next_order = get_next_order(conn)?;
+        diesel::insert_into(hobbies)
+            .values((
+                title.eq(hobby.title),
+                content.eq(hobby.content),
+                image.eq(hobby.image),
+                item_order.eq(hobby.item_order),
+                active.eq(hobby.active),
+                created_at.eq(hobby.created_at),
+                updated_at.eq(hobby.updated_at),
+                deleted_at.eq(hobby.deleted_at),
+                published.eq(hobby.published),
+                order.eq(next_order)
+            ))
+            .get_result(conn)
+    })
 }
 
 fn update_hobby(hobby: HobbyDB, hobby_id: i32, conn: &mut DBPooledConnection) -> Result<HobbyDB, Error> {
@@ -79,7 +89,6 @@ fn update_hobby(hobby: HobbyDB, hobby_id: i32, conn: &mut DBPooledConnection) ->
             active.eq(hobby.active),
             updated_at.eq(Utc::now().naive_utc()),
             published.eq(hobby.published),
-            order.eq(hobby.order)
         ))
         .get_result(conn)
 }
@@ -251,4 +260,20 @@ pub async fn active(query: web::Query<PaginationParams>, pool: web::Data<DBPool>
             .content_type(APPLICATION_JSON)
             .json(serde_json::json!({"message": "Failed to retrieve hobbies"})),
     }
-}
\ No newline at end of file
+}
+
+#[post("/hobbies/reorder")]
+pub async fn reorder(reorder_req: web::Json<ReorderRequest>, pool: web::Data<DBPool>) -> HttpResponse {
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match ordering::reorder(Ordered::Hobbies, &reorder_req.ids, &mut conn) {
+        Ok(reordered) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Hobbies successfully reordered", "reordered": reordered})),
+        Err(ReorderError::Mismatch) => HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "ids must list every hobby exactly once"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to reorder hobbies"})),
+    }
+}
@@ -19,6 +19,46 @@ struct ImageRequest {
     fileloc: String
 }
 
+// Upload paths tried for a file name in turn, the name itself and then its -copyN variants
+pub fn upload_candidates(filename: &str) -> impl Iterator<Item = PathBuf> {
+    let mut c = 0;
+    std::iter::successors(Some(PathBuf::from(format!("./uploads/{}", filename))), move |filepath| {
+        let file_stem = filepath
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let extension = filepath
+            .extension()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+
+        c += 1;
+        let new_filename = if extension.is_empty() {
+            format!("{}-copy{}", file_stem, c)
+        } else {
+            format!("{}-copy{}.{}", file_stem, c, extension)
+        };
+        Some(PathBuf::from(format!("./uploads/{}", new_filename)))
+    })
+}
+
+// Uploaded files never overwrite each other, a taken name gets a -copyN suffix
+pub fn unique_upload_path(filename: &str) -> PathBuf {
+    upload_candidates(filename)
+        .find(|filepath| !filepath.exists())
+        .unwrap_or_default()
+}
+
+// True for an `/assets/...` URL whose file sits in the uploads folder
+pub fn is_uploaded_asset(url: &str) -> bool {
+    match url.strip_prefix("/assets/") {
+        Some(filename) if !filename.is_empty() && !filename.contains("..") => PathBuf::from(format!("./uploads/{}", filename)).is_file(),
+        _ => false,
+    }
+}
+
 async fn save_image(mut payload: Multipart) -> Result<ImageRequest, Error> {
     let mut saved_file: PathBuf = PathBuf::new();
 
@@ -31,29 +71,7 @@ async fn save_image(mut payload: Multipart) -> Result<ImageRequest, Error> {
             .map(|filename| sanitize(filename))
             .unwrap_or_else(|| "upload_file".to_string());
 
-        let mut filepath = PathBuf::from(format!("./uploads/{}", filename));
-
-        let mut c = 1;
-        while filepath.exists() {
-            let file_stem = filepath
-                .file_stem()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .into_owned();
-            let extension = filepath
-                .extension()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .into_owned();
-
-            let new_filename = if extension.is_empty() {
-                format!("{}-copy{}", file_stem, c)
-            } else {
-                format!("{}-copy{}.{}", file_stem, c, extension)
-            };
-            filepath = PathBuf::from(format!("./uploads/{}", new_filename));
-            c += 1;
-        }
+        let filepath = unique_upload_path(filename);
 
         saved_file = filepath.clone();
         let mut f = File::create(filepath)?;
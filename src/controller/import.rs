@@ -0,0 +1,77 @@
+use actix_web::{post, web, HttpResponse};
+use actix_multipart::Multipart;
+use futures_util::stream::StreamExt as _;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::constants::{APPLICATION_JSON, CONNECTION_POOL_ERROR, MAX_IMPORT_SIZE};
+use crate::DBPool;
+
+use crate::importer::{ImportOptions, import_markdown_zip};
+
+// Import Request Struct
+#[derive(Debug, Deserialize)]
+pub struct ImportParams {
+    pub author_id: String,
+    pub dry_run: Option<bool>
+}
+
+// Class Wide Function
+
+// Stops reading once the upload passes MAX_IMPORT_SIZE, the caller rejects anything longer
+async fn read_upload(mut payload: Multipart) -> Result<Vec<u8>, actix_web::Error> {
+    let mut bytes = Vec::new();
+
+    if let Some(item) = payload.next().await {
+        let mut field = item?;
+        while let Some(chunk) = field.next().await {
+            bytes.extend_from_slice(&chunk?);
+            if bytes.len() > MAX_IMPORT_SIZE {
+                break;
+            }
+        }
+    }
+
+    Ok(bytes)
+}
+
+// Routing
+
+#[post("/import/markdown")]
+pub async fn markdown(query: web::Query<ImportParams>, payload: Multipart, pool: web::Data<DBPool>) -> HttpResponse {
+    let options = match Uuid::parse_str(&query.author_id) {
+        Ok(author_id) => ImportOptions { author_id, dry_run: query.dry_run.unwrap_or(false) },
+        Err(e) => return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(e.to_string()),
+    };
+
+    let bytes = match read_upload(payload).await {
+        Ok(bytes) if bytes.len() > MAX_IMPORT_SIZE => return HttpResponse::PayloadTooLarge()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": format!("The zip can't be larger than {} bytes", MAX_IMPORT_SIZE)})),
+        Ok(bytes) if !bytes.is_empty() => bytes,
+        _ => return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Upload a zip of Markdown files"})),
+    };
+
+    // Unzipping, writing the assets and the per file transactions all block
+    let pool = pool.get_ref().clone();
+    let imported = web::block(move || {
+        let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+        import_markdown_zip(bytes, &options, &mut conn)
+    }).await;
+
+    match imported {
+        Ok(Ok(report)) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(report),
+        Ok(Err(e)) => HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error importing posts: {}", e)),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to run the import"})),
+    }
+}
@@ -8,4 +8,13 @@ pub mod role;
 pub mod hobby;
 pub mod setting;
 pub mod image;
-pub mod contact;
\ No newline at end of file
+pub mod contact;
+pub mod view;
+pub mod comment;
+pub mod feed;
+pub mod sitemap;
+pub mod series;
+pub mod translation;
+pub mod reaction;
+pub mod import;
+pub mod export;
\ No newline at end of file
@@ -1,40 +1,127 @@
-use actix_web::{post, get, delete, web, HttpResponse};
-use chrono::{Utc, NaiveDateTime};
+use actix_web::{post, get, delete, web, HttpRequest, HttpResponse};
+use actix_web::http::header;
+use chrono::{Utc, NaiveDate, NaiveDateTime, Months};
 use serde::{Serialize, Deserialize};
-use diesel::result::Error;
-use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, Queryable, PgTextExpressionMethods, BoolExpressionMethods};
+use diesel::result::{Error, DatabaseErrorKind};
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, PgTextExpressionMethods, BoolExpressionMethods, TextExpressionMethods, Connection, JoinOnDsl, SelectableHelper, Queryable, QueryableByName, Selectable, PgSortExpressionMethods};
+use diesel::sql_types::{BigInt, Integer, Text};
 use uuid::Uuid;
+use log::error;
+use std::collections::BTreeMap;
 
 use crate::constants::{APPLICATION_JSON, CONNECTION_POOL_ERROR};
+use crate::errors::SlugError;
+use crate::slug::{slugify, unique_slug};
+use crate::render::{markdown_to_text, excerpt as cut_excerpt};
+use crate::blocks::{Block, validate_blocks, blocks_to_markdown, blocks_to_html};
+use crate::shortcode::{Embeds, render_content, shortcode_warnings, strip_shortcodes};
 use crate::{DBPool, DBPooledConnection};
 
 use crate::models::PostDB;
 use crate::models::PostCatDB;
-use crate::models::UserDB;
+use crate::models::PostAuthorDB;
+use crate::controller::view::record_view;
+use crate::controller::reaction::reaction_counts;
+use crate::controller::image::is_uploaded_asset;
+use crate::controller::setting::{SiteSettings, get_setting_value};
+use crate::controller::postcat::category_with_descendants;
+use crate::controller::series::{SeriesNav, series_nav};
+use crate::controller::translation::{PostAlternate, LocaleParams, localize_posts, requested_locales, translation_by_slug};
+
+// Contributor roles a post_authors row can hold, the lead author is always "author"
+const AUTHOR_ROLES: [&str; 3] = ["author", "editor", "reviewer"];
+
+// A global pin tops every listing, a category pin only the listing of its own category
+const PIN_SCOPES: [&str; 2] = ["global", "category"];
 
 // Post Request Struct
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ContributorRequest {
+    pub user_id: String,
+    pub role: String
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PostRequest {
     pub title: String,
     pub subtitle: Option<String>,
-    pub slug: String,
+    pub slug: Option<String>,
+    #[serde(default)]
     pub content: String,
+    pub content_blocks: Option<Vec<Block>>,
     pub category_id: i32,
     pub tags: Option<String>,
     pub author_id: String,
-    pub published: bool
+    pub contributors: Option<Vec<ContributorRequest>>,
+    pub published: bool,
+    pub cover_image: Option<String>,
+    pub meta_title: Option<String>,
+    pub meta_description: Option<String>,
+    pub canonical_url: Option<String>,
+    pub og_image: Option<String>,
+    pub excerpt: Option<String>
+}
+
+// Blank optional fields are stored as NULL so the SEO defaults kick in
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_ref().map(|value| value.trim().to_string()).filter(|value| !value.is_empty())
+}
+
+fn is_absolute_url(url: &str) -> bool {
+    let host = url.strip_prefix("https://").or(url.strip_prefix("http://")).and_then(|rest| rest.split(['/', '?', '#']).next());
+    host.is_some_and(|host| !host.is_empty()) && !url.contains(char::is_whitespace)
 }
 
 impl PostRequest {
+    fn validate_seo(&self) -> Result<(), String> {
+        if let Some(cover_image) = non_empty(&self.cover_image) {
+            if !is_uploaded_asset(&cover_image) {
+                return Err(format!("Cover image {} isn't an uploaded /assets image", cover_image));
+            }
+        }
+
+        if let Some(og_image) = non_empty(&self.og_image) {
+            if (!is_uploaded_asset(&og_image) && !is_absolute_url(&og_image)) || og_image.chars().count() > 255 {
+                return Err(format!("Open Graph image {} must be an uploaded /assets image or an absolute URL of up to 255 characters", og_image));
+            }
+        }
+
+        if let Some(canonical_url) = non_empty(&self.canonical_url) {
+            if !is_absolute_url(&canonical_url) || canonical_url.chars().count() > 255 {
+                return Err(format!("Canonical URL {} must be an absolute http(s) URL of up to 255 characters", canonical_url));
+            }
+        }
+
+        if non_empty(&self.meta_title).map_or(0, |meta_title| meta_title.chars().count()) > 70 {
+            return Err("Meta title can't be longer than 70 characters".to_string());
+        }
+
+        if non_empty(&self.meta_description).map_or(0, |meta_description| meta_description.chars().count()) > 160 {
+            return Err("Meta description can't be longer than 160 characters".to_string());
+        }
+
+        Ok(())
+    }
+
     pub fn to_post_db(&self) -> Result<PostDB, String> {
         let author_id = Uuid::parse_str(&self.author_id).map_err(|e| e.to_string())?;
+        self.validate_seo()?;
+
+        // Block content wins over a Markdown blob, its Markdown rendering is kept in `content`
+        let (content, content_blocks) = match &self.content_blocks {
+            Some(blocks) => {
+                validate_blocks(blocks)?;
+                (blocks_to_markdown(blocks), Some(serde_json::to_value(blocks).map_err(|e| e.to_string())?))
+            }
+            None => (self.content.clone(), None),
+        };
 
         Ok(PostDB {
             id: 1,
             title: self.title.clone(),
             subtitle: Some(self.subtitle.clone().unwrap_or("".to_string())),
-            slug: self.slug.clone(),
-            content: self.content.clone(),
+            slug: slugify(&self.slug.clone().unwrap_or_default()),
+            content,
             category_id: self.category_id.clone(),
             tags: Some(self.tags.clone().unwrap_or("".to_string())),
             author_id,
@@ -42,16 +129,153 @@ impl PostRequest {
             updated_at: Utc::now().naive_utc(),
             deleted_at: None,
             published: self.published,
+            cover_image: non_empty(&self.cover_image),
+            meta_title: non_empty(&self.meta_title),
+            meta_description: non_empty(&self.meta_description),
+            canonical_url: non_empty(&self.canonical_url),
+            og_image: non_empty(&self.og_image),
+            pin_scope: None,
+            pinned_until: None,
+            featured: false,
+            featured_order: None,
+            excerpt: non_empty(&self.excerpt),
+            content_blocks,
         })
     }
+
+    // Contributors besides the lead author, None leaves the saved ones untouched
+    pub fn to_contributors(&self) -> Result<Option<Vec<(Uuid, String)>>, String> {
+        let contributors = match &self.contributors {
+            Some(contributors) => contributors,
+            None => return Ok(None),
+        };
+
+        let mut result = Vec::new();
+        for contributor in contributors {
+            let user_id = Uuid::parse_str(&contributor.user_id).map_err(|e| e.to_string())?;
+            if !AUTHOR_ROLES.contains(&contributor.role.as_str()) {
+                return Err(format!("Unknown contributor role: {}", contributor.role));
+            }
+            result.push((user_id, contributor.role.clone()));
+        }
+
+        Ok(Some(result))
+    }
+}
+
+// Public profile of a post author, users' emails and phones stay private
+#[derive(Debug, Serialize, Queryable)]
+pub struct PostAuthor {
+    #[serde(skip)]
+    pub post_id: i32,
+    pub id: Uuid,
+    pub name: String,
+    pub github: Option<String>,
+    pub linkedin: Option<String>,
+    pub role: String,
+    pub order: i32
+}
+
+// Public profile of the lead author, selected without the user's email, phone and birth date
+#[derive(Debug, Serialize, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::users)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PostUser {
+    pub id: Uuid,
+    pub name: String,
+    pub github: Option<String>,
+    pub linkedin: Option<String>
+}
+
+// Share preview values with the title, subtitle and cover standing in for blank fields
+#[derive(Debug, Serialize)]
+pub struct SeoMeta {
+    pub title: String,
+    pub description: String,
+    pub image: Option<String>,
+    pub canonical_url: Option<String>
+}
+
+impl SeoMeta {
+    pub fn from_post(post: &PostDB) -> SeoMeta {
+        SeoMeta {
+            title: post.meta_title.clone().unwrap_or(post.title.clone()),
+            description: post.meta_description.clone().or(post.subtitle.clone()).unwrap_or_default(),
+            image: post.og_image.clone().or(post.cover_image.clone()),
+            canonical_url: post.canonical_url.clone()
+        }
+    }
 }
 
-#[derive(Queryable, Debug, Serialize)]
+#[derive(Debug, Serialize)]
 pub struct JoinedPost {
     #[serde(flatten)]
     pub post: PostDB,
     pub category: PostCatDB,
-    pub user: UserDB
+    pub user: PostUser,
+    pub authors: Vec<PostAuthor>,
+    pub seo: SeoMeta,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series: Option<SeriesNav>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternates: Vec<PostAlternate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reactions: Option<BTreeMap<String, i64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_html: Option<String>
+}
+
+impl JoinedPost {
+    pub fn new((post, category, user): (PostDB, PostCatDB, PostUser)) -> JoinedPost {
+        JoinedPost {
+            seo: SeoMeta::from_post(&post),
+            post,
+            category,
+            user,
+            authors: Vec::new(),
+            series: None,
+            locale: None,
+            alternates: Vec::new(),
+            reactions: None,
+            content_html: None
+        }
+    }
+
+    pub fn author_names(&self) -> Vec<String> {
+        self.authors
+            .iter()
+            .filter(|author| author.role == "author")
+            .map(|author| author.name.clone())
+            .collect()
+    }
+}
+
+// Renders the contents with their shortcodes expanded, looking the embeds up for all posts at once
+pub fn render_posts_html(joined_posts: &mut [JoinedPost], site: &SiteSettings, conn: &mut DBPooledConnection) -> Result<(), Error> {
+    let contents: Vec<&str> = joined_posts.iter().map(|joined| joined.post.content.as_str()).collect();
+    let embeds = Embeds::load(&contents, site, conn)?;
+
+    for joined in joined_posts.iter_mut() {
+        // Block posts render from their blocks, their Markdown is only kept for listings and exports
+        let blocks = joined.post.content_blocks.clone().and_then(|value| serde_json::from_value::<Vec<Block>>(value).ok());
+        joined.content_html = Some(match blocks {
+            Some(blocks) => blocks_to_html(&blocks, site, &embeds),
+            None => render_content(&joined.post.content, site, &embeds),
+        });
+    }
+
+    Ok(())
+}
+
+// A saved post along with the shortcodes that couldn't be resolved
+#[derive(Debug, Serialize)]
+pub struct SavedPost {
+    #[serde(flatten)]
+    pub post: PostDB,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>
 }
 
 // Pagination Request Struct
@@ -60,50 +284,337 @@ pub struct PaginationParams {
     pub page: Option<i32>,
     pub limit: Option<i32>,
     pub cat: Option<String>,
-    pub search: Option<String>
+    pub search: Option<String>,
+    pub lang: Option<String>,
+    pub descendants: Option<bool>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ArchiveParams {
+    pub cat: Option<String>
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveMonth {
+    pub month: u32,
+    pub count: i64
+}
+
+#[derive(QueryableByName)]
+struct ArchiveCount {
+    #[diesel(sql_type = Integer)]
+    year: i32,
+    #[diesel(sql_type = Integer)]
+    month: i32,
+    #[diesel(sql_type = BigInt)]
+    count: i64
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveYear {
+    pub year: i32,
+    pub count: i64,
+    pub months: Vec<ArchiveMonth>
+}
+
+// Optional narrowing of a post listing, the default lists every post
+#[derive(Debug, Default)]
+pub struct PostFilter {
+    pub cat: String,
+    pub with_descendants: bool,
+    pub search: String,
+    pub author: Option<Uuid>,
+    pub created_between: Option<(NaiveDateTime, NaiveDateTime)>,
+    pub is_published: bool,
+    pub pinned_first: bool,
+    pub featured_only: bool
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthorPostsParams {
+    pub page: Option<i32>,
+    pub limit: Option<i32>,
+    pub lang: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeaturedParams {
+    pub limit: Option<i32>,
+    pub lang: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PinRequest {
+    pub scope: String,
+    pub until: Option<NaiveDateTime>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeaturedRequest {
+    pub post_ids: Vec<i32>
 }
 
 // Class Wide Function
-fn create_post(post: PostDB, conn: &mut DBPooledConnection) -> Result<PostDB, Error> {
-    use crate::schema::posts::dsl::*;
-    diesel::insert_into(posts)
+
+// Every slug a post or its translations has used, other posts can't take them so old links keep resolving
+fn taken_slugs(base: &str, own_post_id: Option<i32>, conn: &mut DBPooledConnection) -> Result<Vec<String>, Error> {
+    use crate::schema::post_slugs::dsl::*;
+    use crate::schema::post_translations::dsl::{post_translations, slug as translation_slug, post_id as translation_post_id};
+
+    let mut query = post_slugs
+        .filter(slug.eq(base).or(slug.like(format!("{}-%", base))))
+        .select(slug)
+        .into_boxed();
+
+    let mut translation_query = post_translations
+        .filter(translation_slug.eq(base).or(translation_slug.like(format!("{}-%", base))))
+        .select(translation_slug)
+        .into_boxed();
+
+    if let Some(own_post_id) = own_post_id {
+        query = query.filter(post_id.ne(own_post_id));
+        translation_query = translation_query.filter(translation_post_id.ne(own_post_id));
+    }
+
+    let mut taken = query.load::<String>(conn)?;
+    taken.extend(translation_query.load::<String>(conn)?);
+
+    Ok(taken)
+}
+
+// `base` or the first `base-N` no other post has used, for imports that go on when their slug is taken
+pub fn free_post_slug(base: &str, own_post_id: Option<i32>, conn: &mut DBPooledConnection) -> Result<String, Error> {
+    Ok(unique_slug(base, &taken_slugs(base, own_post_id, conn)?))
+}
+
+fn resolve_slug(requested: &str, title: &str, own_post_id: Option<i32>, conn: &mut DBPooledConnection) -> Result<String, SlugError> {
+    if !requested.is_empty() {
+        if taken_slugs(requested, own_post_id, conn)?.iter().any(|taken| taken == requested) {
+            return Err(SlugError::Taken(requested.to_string()));
+        }
+        return Ok(requested.to_string());
+    }
+
+    let base = match slugify(title) {
+        generated if generated.is_empty() => "post".to_string(),
+        generated => generated,
+    };
+
+    Ok(unique_slug(&base, &taken_slugs(&base, own_post_id, conn)?))
+}
+
+pub fn record_slug(slug_post_id: i32, post_slug: &str, conn: &mut DBPooledConnection) -> Result<usize, Error> {
+    use crate::schema::post_slugs::dsl::*;
+
+    diesel::insert_into(post_slugs)
         .values((
-            title.eq(post.title),
-            subtitle.eq(post.subtitle),
-            slug.eq(post.slug),
-            content.eq(post.content),
-            category_id.eq(post.category_id),
-            tags.eq(post.tags),
-            author_id.eq(post.author_id),
-            created_at.eq(post.created_at),
-            updated_at.eq(post.updated_at),
-            deleted_at.eq(post.deleted_at),
-            published.eq(post.published),
+            post_id.eq(slug_post_id),
+            slug.eq(post_slug),
+            created_at.eq(Utc::now().naive_utc()),
         ))
-        .get_result(conn)
+        .on_conflict_do_nothing()
+        .execute(conn)
 }
 
-fn update_post(post: PostDB, post_id: i32, conn: &mut DBPooledConnection) -> Result<PostDB, Error> {
+pub fn set_post_authors(authors_post_id: i32, lead_author_id: Uuid, contributors: Option<Vec<(Uuid, String)>>, conn: &mut DBPooledConnection) -> Result<usize, Error> {
+    use crate::schema::post_authors::dsl::*;
+
+    let contributors = match contributors {
+        Some(contributors) => contributors,
+        None => post_authors
+            .filter(post_id.eq(authors_post_id))
+            .filter(author_order.gt(1))
+            .order_by(author_order.asc())
+            .select((user_id, role))
+            .load::<(Uuid, String)>(conn)?,
+    };
+
+    let mut rows = vec![PostAuthorDB {
+        post_id: authors_post_id,
+        user_id: lead_author_id,
+        role: "author".to_string(),
+        author_order: 1,
+    }];
+    for (contributor_id, contributor_role) in contributors {
+        if rows.iter().any(|row| row.user_id == contributor_id) {
+            continue;
+        }
+        rows.push(PostAuthorDB {
+            post_id: authors_post_id,
+            user_id: contributor_id,
+            role: contributor_role,
+            author_order: rows.len() as i32 + 1,
+        });
+    }
+
+    diesel::delete(post_authors.filter(post_id.eq(authors_post_id))).execute(conn)?;
+    diesel::insert_into(post_authors).values(&rows).execute(conn)
+}
+
+fn attach_authors(joined_posts: &mut [JoinedPost], conn: &mut DBPooledConnection) -> Result<(), Error> {
+    use crate::schema::post_authors::dsl::{post_authors, post_id, role, author_order};
+    use crate::schema::users::dsl::{users, id, name, github, linkedin, deleted_at};
+
+    let post_ids: Vec<i32> = joined_posts.iter().map(|joined| joined.post.id).collect();
+    let authors = post_authors
+        .inner_join(users)
+        .filter(post_id.eq_any(post_ids))
+        .filter(deleted_at.is_null())
+        .order_by((post_id.asc(), author_order.asc()))
+        .select((post_id, id, name, github, linkedin, role, author_order))
+        .load::<PostAuthor>(conn)?;
+
+    for author in authors {
+        if let Some(joined) = joined_posts.iter_mut().find(|joined| joined.post.id == author.post_id) {
+            joined.authors.push(author);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn create_post(post: PostDB, contributors: Option<Vec<(Uuid, String)>>, conn: &mut DBPooledConnection) -> Result<PostDB, SlugError> {
     use crate::schema::posts::dsl::*;
-    diesel::update(posts.filter(id.eq(post_id)))
-        .set((
-            title.eq(post.title),
-            subtitle.eq(post.subtitle),
-            slug.eq(post.slug),
-            content.eq(post.content),
-            category_id.eq(post.category_id),
-            tags.eq(post.tags),
-            author_id.eq(post.author_id),
-            updated_at.eq(Utc::now().naive_utc()),
-            published.eq(post.published)
-        ))
-        .get_result(conn)
+
+    conn.transaction(|conn| {
+        let new_slug = resolve_slug(&post.slug, &post.title, None, conn)?;
+
+        let inserted_post: PostDB = diesel::insert_into(posts)
+            .values((
+                title.eq(post.title),
+                subtitle.eq(post.subtitle),
+                slug.eq(new_slug),
+                content.eq(post.content),
+                category_id.eq(post.category_id),
+                tags.eq(post.tags),
+                author_id.eq(post.author_id),
+                created_at.eq(post.created_at),
+                updated_at.eq(post.updated_at),
+                deleted_at.eq(post.deleted_at),
+                published.eq(post.published),
+                cover_image.eq(post.cover_image),
+                meta_title.eq(post.meta_title),
+                meta_description.eq(post.meta_description),
+                canonical_url.eq(post.canonical_url),
+                og_image.eq(post.og_image),
+                excerpt.eq(post.excerpt),
+                content_blocks.eq(post.content_blocks),
+            ))
+            .get_result(conn)?;
+
+        record_slug(inserted_post.id, &inserted_post.slug, conn)?;
+        set_post_authors(inserted_post.id, inserted_post.author_id, contributors, conn)?;
+
+        Ok(inserted_post)
+    })
+}
+
+pub fn update_post(post: PostDB, contributors: Option<Vec<(Uuid, String)>>, post_id: i32, conn: &mut DBPooledConnection) -> Result<PostDB, SlugError> {
+    use crate::schema::posts::dsl::*;
+
+    conn.transaction(|conn| {
+        // Omitting the slug on update keeps the current one instead of following the title
+        let new_slug = if post.slug.is_empty() {
+            posts.filter(id.eq(post_id)).select(slug).first::<String>(conn)?
+        } else {
+            resolve_slug(&post.slug, &post.title, Some(post_id), conn)?
+        };
+
+        let updated_post: PostDB = diesel::update(posts.filter(id.eq(post_id)))
+            .set((
+                title.eq(post.title),
+                subtitle.eq(post.subtitle),
+                slug.eq(new_slug),
+                content.eq(post.content),
+                category_id.eq(post.category_id),
+                tags.eq(post.tags),
+                author_id.eq(post.author_id),
+                updated_at.eq(Utc::now().naive_utc()),
+                published.eq(post.published),
+                cover_image.eq(post.cover_image),
+                meta_title.eq(post.meta_title),
+                meta_description.eq(post.meta_description),
+                canonical_url.eq(post.canonical_url),
+                og_image.eq(post.og_image),
+                excerpt.eq(post.excerpt),
+                content_blocks.eq(post.content_blocks),
+            ))
+            .get_result(conn)?;
+
+        record_slug(updated_post.id, &updated_post.slug, conn)?;
+        set_post_authors(updated_post.id, updated_post.author_id, contributors, conn)?;
+
+        Ok(updated_post)
+    })
+}
+
+// Listings carry an excerpt instead of the full content, a manual excerpt wins over the generated one
+pub fn to_listing(joined_posts: &mut [JoinedPost], conn: &mut DBPooledConnection) {
+    let max_chars = get_setting_value("excerpt_length", conn)
+        .and_then(|length| length.parse::<usize>().ok())
+        .unwrap_or(200);
+
+    for joined in joined_posts.iter_mut() {
+        if joined.post.excerpt.is_none() {
+            joined.post.excerpt = Some(cut_excerpt(&markdown_to_text(&strip_shortcodes(&joined.post.content)), max_chars));
+        }
+        joined.post.content.clear();
+        joined.post.content_blocks = None;
+    }
+}
+
+// Replaces the featured posts with the given ones, in the given order
+fn set_featured_posts(ordered_post_ids: &[i32], conn: &mut DBPooledConnection) -> Result<usize, Error> {
+    use crate::schema::posts::dsl::*;
+
+    conn.transaction(|conn| {
+        diesel::update(posts.filter(featured.eq(true)))
+            .set((featured.eq(false), featured_order.eq(None::<i32>)))
+            .execute(conn)?;
+
+        for (position, featured_post_id) in ordered_post_ids.iter().enumerate() {
+            let updated = diesel::update(posts.filter(id.eq(featured_post_id)).filter(deleted_at.is_null()))
+                .set((featured.eq(true), featured_order.eq(position as i32 + 1)))
+                .execute(conn)?;
+            // Rolls the whole reorder back instead of leaving a partial list
+            if updated == 0 {
+                return Err(Error::NotFound);
+            }
+        }
+
+        Ok(ordered_post_ids.len())
+    })
+}
+
+fn saved_post(post: PostDB, conn: &mut DBPooledConnection) -> SavedPost {
+    let warnings = shortcode_warnings(&post.content, conn).unwrap_or_else(|e| {
+        error!("Checking post shortcodes failed: {:?}", e);
+        Vec::new()
+    });
+
+    SavedPost { post, warnings }
+}
+
+fn save_error_response(action: &str, e: SlugError) -> HttpResponse {
+    match e {
+        SlugError::Taken(_) | SlugError::DatabaseError(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => HttpResponse::Conflict()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error {} post: {}", action, e)),
+        // Unknown author, contributor or category ids
+        SlugError::DatabaseError(Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, ref info)) => HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error {} post: {}", action, info.details().unwrap_or(info.message()))),
+        _ => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error {} post: {}", action, e)),
+    }
 }
 
-fn all_post_with_pagination(page: i32, limit: i32, cat: String, search: String, is_published: bool, conn: &mut DBPooledConnection) -> Result<Vec<JoinedPost>, Error> {
+pub fn all_post_with_pagination(page: i32, limit: i32, filter: PostFilter, conn: &mut DBPooledConnection) -> Result<Vec<JoinedPost>, Error> {
     use crate::schema::posts::dsl::*;
     use crate::schema::post_categories::dsl::{post_categories, deleted_at as category_deleted_at, slug as category_slug};
     use crate::schema::users::dsl::{users, deleted_at as user_deleted_at};
+    use crate::schema::post_authors::dsl::{post_authors, post_id as author_post_id, user_id as author_user_id};
     
     let mut query = posts
         .inner_join(post_categories)
@@ -114,12 +625,34 @@ fn all_post_with_pagination(page: i32, limit: i32, cat: String, search: String,
         .order_by(id.desc())
         .limit(limit as i64)
         .offset(((page - 1) * limit) as i64)
+        .select((PostDB::as_select(), PostCatDB::as_select(), PostUser::as_select()))
         .into_boxed();
 
-    if !cat.is_empty() {
-        query = query.filter(category_slug.eq(cat));
+    if let Some(author) = filter.author {
+        query = query.filter(id.eq_any(post_authors.filter(author_user_id.eq(author)).select(author_post_id)));
     }
 
+    if let Some((from, until)) = filter.created_between {
+        query = query.filter(created_at.ge(from)).filter(created_at.lt(until));
+    }
+
+    // Unexpired pins go first, category pins only count while listing a single category
+    if filter.featured_only {
+        query = query.filter(featured.eq(true)).order_by((featured_order.asc(), id.desc()));
+    } else if filter.pinned_first {
+        let scopes = if filter.cat.is_empty() { &PIN_SCOPES[..1] } else { &PIN_SCOPES[..] };
+        let pinned = pin_scope.eq_any(scopes)
+            .and(pinned_until.is_null().or(pinned_until.gt(Utc::now().naive_utc())));
+        query = query.order_by((pinned.desc().nulls_last(), id.desc()));
+    }
+
+    if filter.with_descendants && !filter.cat.is_empty() {
+        query = query.filter(category_id.eq_any(category_with_descendants(&filter.cat, conn)?));
+    } else if !filter.cat.is_empty() {
+        query = query.filter(category_slug.eq(filter.cat));
+    }
+
+    let search = filter.search;
     if !search.is_empty() {
         query = query.filter(
             title.ilike(format!("%{}%", search))
@@ -128,58 +661,131 @@ fn all_post_with_pagination(page: i32, limit: i32, cat: String, search: String,
         );
     }
 
-    if is_published {
-        query = query.filter(published.eq(is_published));
+    if filter.is_published {
+        query = query.filter(published.eq(true));
     }
 
-    let result: Vec<JoinedPost> = query
-        .load::<(PostDB, PostCatDB, UserDB)>(conn)?
+    let mut result: Vec<JoinedPost> = query
+        .load::<(PostDB, PostCatDB, PostUser)>(conn)?
         .into_iter()
-        .map(|(post, category, user)| JoinedPost { post, category, user })
+        .map(JoinedPost::new)
         .collect();
+    attach_authors(&mut result, conn)?;
 
     Ok(result)
 }
 
-fn get_single_post(post_id: i32, conn: &mut DBPooledConnection) -> Result<JoinedPost, Error> {
+pub fn get_posts_by_ids(post_ids: Vec<i32>, conn: &mut DBPooledConnection) -> Result<Vec<JoinedPost>, Error> {
     use crate::schema::posts::dsl::*;
     use crate::schema::post_categories::dsl::{post_categories};
     use crate::schema::users::dsl::{users};
 
-    posts
+    let mut result: Vec<JoinedPost> = posts
         .inner_join(post_categories)
         .inner_join(users)
-        .filter(id.eq(post_id))
-        .limit(1)
-        .get_result(conn)
+        .filter(id.eq_any(post_ids))
+        .select((PostDB::as_select(), PostCatDB::as_select(), PostUser::as_select()))
+        .load::<(PostDB, PostCatDB, PostUser)>(conn)?
+        .into_iter()
+        .map(JoinedPost::new)
+        .collect();
+    attach_authors(&mut result, conn)?;
+
+    Ok(result)
 }
 
-fn get_single_post_by_slug(post_slug: String, conn: &mut DBPooledConnection) -> Result<JoinedPost, Error> {
+fn load_single_post(post_id: i32, published_only: bool, conn: &mut DBPooledConnection) -> Result<JoinedPost, Error> {
     use crate::schema::posts::dsl::*;
     use crate::schema::post_categories::dsl::{post_categories};
     use crate::schema::users::dsl::{users};
 
-    posts
+    let joined = posts
         .inner_join(post_categories)
         .inner_join(users)
-        .filter(slug.eq(post_slug))
-        .limit(1)
-        .get_result(conn)
+        .filter(id.eq(post_id))
+        .select((PostDB::as_select(), PostCatDB::as_select(), PostUser::as_select()))
+        .first::<(PostDB, PostCatDB, PostUser)>(conn)?;
+
+    let mut post = JoinedPost::new(joined);
+    attach_authors(std::slice::from_mut(&mut post), conn)?;
+    post.series = series_nav(post.post.id, published_only, conn)?;
+
+    Ok(post)
+}
+
+fn get_single_post(post_id: i32, conn: &mut DBPooledConnection) -> Result<JoinedPost, Error> {
+    load_single_post(post_id, false, conn)
+}
+
+// Published post counts per month, newest first, grouped under their year
+fn archive_counts(cat: String, conn: &mut DBPooledConnection) -> Result<Vec<ArchiveYear>, Error> {
+    let counts: Vec<ArchiveCount> = diesel::sql_query(
+        "SELECT date_part('year', posts.created_at)::int AS year, date_part('month', posts.created_at)::int AS month, count(*) AS count \
+         FROM posts \
+         INNER JOIN post_categories ON post_categories.id = posts.category_id \
+         INNER JOIN users ON users.id = posts.author_id \
+         WHERE posts.deleted_at IS NULL AND posts.published \
+         AND post_categories.deleted_at IS NULL AND users.deleted_at IS NULL \
+         AND ($1 = '' OR post_categories.slug = $1) \
+         GROUP BY 1, 2 \
+         ORDER BY 1 DESC, 2 DESC"
+    )
+    .bind::<Text, _>(cat)
+    .load(conn)?;
+
+    let mut years: Vec<ArchiveYear> = Vec::new();
+    for ArchiveCount { year, month, count } in counts {
+        let month = month as u32;
+        match years.last_mut() {
+            Some(archive_year) if archive_year.year == year => {
+                archive_year.count += count;
+                archive_year.months.push(ArchiveMonth { month, count });
+            }
+            _ => years.push(ArchiveYear { year, count, months: vec![ArchiveMonth { month, count }] }),
+        }
+    }
+
+    Ok(years)
+}
+
+fn get_current_slug(old_slug: String, conn: &mut DBPooledConnection) -> Result<String, Error> {
+    use crate::schema::post_slugs::dsl::{post_slugs, slug as old_slugs, post_id};
+    use crate::schema::posts::dsl::*;
+
+    posts
+        .inner_join(post_slugs.on(post_id.eq(id)))
+        .filter(old_slugs.eq(old_slug))
+        .select(slug)
+        .first::<String>(conn)
+}
+
+// Finds a post by its own slug or a translated one, the latter also tells which locale was linked
+fn get_single_post_by_slug(post_slug: String, conn: &mut DBPooledConnection) -> Result<(JoinedPost, Option<String>), Error> {
+    use crate::schema::posts::dsl::*;
+
+    let (found_id, slug_locale) = match posts.filter(slug.eq(&post_slug)).select(id).first::<i32>(conn) {
+        Ok(found_id) => (found_id, None),
+        Err(Error::NotFound) => {
+            let (found_id, found_locale) = translation_by_slug(&post_slug, conn)?;
+            (found_id, Some(found_locale))
+        }
+        Err(e) => return Err(e),
+    };
+
+    Ok((load_single_post(found_id, true, conn)?, slug_locale))
 }
 
 // Routing
 #[post("/post")]
 pub async fn create(post_req: web::Json<PostRequest>, pool: web::Data<DBPool>) -> HttpResponse {
-    match post_req.to_post_db() {
-        Ok(post_db) => {
+    match post_req.to_post_db().and_then(|post_db| Ok((post_db, post_req.to_contributors()?))) {
+        Ok((post_db, contributors)) => {
             let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
-            match create_post(post_db, &mut conn) {
+            match create_post(post_db, contributors, &mut conn) {
                 Ok(inserted_post) => HttpResponse::Created()
                     .content_type(APPLICATION_JSON)
-                    .json(inserted_post),
-                Err(e) => HttpResponse::InternalServerError()
-                    .content_type(APPLICATION_JSON)
-                    .json(format!("Error inserting post: {}", e)),
+                    .json(saved_post(inserted_post, &mut conn)),
+                Err(e) => save_error_response("inserting", e),
             }
         }
         Err(e) => HttpResponse::BadRequest()
@@ -191,16 +797,14 @@ pub async fn create(post_req: web::Json<PostRequest>, pool: web::Data<DBPool>) -
 #[post("/post/{id}")]
 pub async fn update(path: web::Path<i32>, post_req: web::Json<PostRequest>, pool: web::Data<DBPool>) -> HttpResponse {
     let post_id = path.into_inner();
-    match post_req.to_post_db() {
-        Ok(post_db) => {
+    match post_req.to_post_db().and_then(|post_db| Ok((post_db, post_req.to_contributors()?))) {
+        Ok((post_db, contributors)) => {
             let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
-            match update_post(post_db, post_id, &mut conn) {
+            match update_post(post_db, contributors, post_id, &mut conn) {
                 Ok(updated_post) => HttpResponse::Created()
                     .content_type(APPLICATION_JSON)
-                    .json(updated_post),
-                Err(e) => HttpResponse::InternalServerError()
-                    .content_type(APPLICATION_JSON)
-                    .json(format!("Error updating post: {}", e)),
+                    .json(saved_post(updated_post, &mut conn)),
+                Err(e) => save_error_response("updating", e),
             }
         }
         Err(e) => HttpResponse::BadRequest()
@@ -245,6 +849,64 @@ pub async fn delete(path: web::Path<i32>, pool: web::Data<DBPool>) -> HttpRespon
     }
 }
 
+#[post("/post/{id}/pin")]
+pub async fn pin(path: web::Path<i32>, pin_req: web::Json<PinRequest>, pool: web::Data<DBPool>) -> HttpResponse {
+    let post_id = path.into_inner();
+    let pin_req = pin_req.into_inner();
+
+    if !PIN_SCOPES.contains(&pin_req.scope.as_str()) {
+        return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": format!("Pin scope must be one of: {}", PIN_SCOPES.join(", "))}));
+    }
+    if pin_req.until.is_some_and(|until| until <= Utc::now().naive_utc()) {
+        return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Pin expiry must be in the future"}));
+    }
+
+    use crate::schema::posts::dsl::*;
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match diesel::update(posts.filter(id.eq(post_id)).filter(deleted_at.is_null()))
+        .set((pin_scope.eq(Some(pin_req.scope)), pinned_until.eq(pin_req.until)))
+        .execute(&mut conn)
+    {
+        Ok(0) => HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Post not found"})),
+        Ok(_) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Post successfully pinned"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to pin post"})),
+    }
+}
+
+#[delete("/post/{id}/pin")]
+pub async fn unpin(path: web::Path<i32>, pool: web::Data<DBPool>) -> HttpResponse {
+    let post_id = path.into_inner();
+
+    use crate::schema::posts::dsl::*;
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match diesel::update(posts.filter(id.eq(post_id)))
+        .set((pin_scope.eq(None::<String>), pinned_until.eq(None::<NaiveDateTime>)))
+        .execute(&mut conn)
+    {
+        Ok(0) => HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Post not found"})),
+        Ok(_) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Post successfully unpinned"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to unpin post"})),
+    }
+}
+
 #[post("/post/{id}/restore")]
 pub async fn restore(path: web::Path<i32>, pool: web::Data<DBPool>) -> HttpResponse {
     let post_id = path.into_inner();
@@ -273,10 +935,14 @@ pub async fn all(query: web::Query<PaginationParams>, pool: web::Data<DBPool>) -
     let search = query.search.clone().unwrap_or("".to_string());
 
     let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
-    match all_post_with_pagination(page, limit, cat, search, false, &mut conn) {
-        Ok(posts) => HttpResponse::Ok()
-            .content_type(APPLICATION_JSON)
-            .json(posts),
+    let filter = PostFilter { cat, search, ..Default::default() };
+    match all_post_with_pagination(page, limit, filter, &mut conn) {
+        Ok(mut posts) => {
+            to_listing(&mut posts, &mut conn);
+            HttpResponse::Ok()
+                .content_type(APPLICATION_JSON)
+                .json(posts)
+        }
         Err(_) => HttpResponse::InternalServerError()
             .content_type(APPLICATION_JSON)
             .json(serde_json::json!({"message": "Failed to retrieve posts"})),
@@ -284,17 +950,143 @@ pub async fn all(query: web::Query<PaginationParams>, pool: web::Data<DBPool>) -
 }
 
 #[get("/posts/active")]
-pub async fn active(query: web::Query<PaginationParams>, pool: web::Data<DBPool>) -> HttpResponse {
+pub async fn active(query: web::Query<PaginationParams>, http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
     let page = query.page.unwrap_or(1);
     let limit = query.limit.unwrap_or(20);
     let cat = query.cat.clone().unwrap_or("".to_string());
     let search = query.search.clone().unwrap_or("".to_string());
 
     let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
-    match all_post_with_pagination(page, limit, cat, search, true, &mut conn) {
-        Ok(posts) => HttpResponse::Ok()
+    let requested = requested_locales(query.lang.as_deref(), &http_req);
+    let with_descendants = query.descendants.unwrap_or(false);
+    let filter = PostFilter { cat, with_descendants, search, is_published: true, pinned_first: true, ..Default::default() };
+    match all_post_with_pagination(page, limit, filter, &mut conn)
+        .and_then(|mut posts| localize_posts(&mut posts, &requested, &mut conn).map(|_| posts))
+    {
+        Ok(mut posts) => {
+            to_listing(&mut posts, &mut conn);
+            HttpResponse::Ok()
+                .content_type(APPLICATION_JSON)
+                .json(posts)
+        }
+        Err(_) => HttpResponse::InternalServerError()
             .content_type(APPLICATION_JSON)
-            .json(posts),
+            .json(serde_json::json!({"message": "Failed to retrieve posts"})),
+    }
+}
+
+#[get("/posts/featured")]
+pub async fn featured(query: web::Query<FeaturedParams>, http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
+    let limit = query.limit.unwrap_or(5);
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    let requested = requested_locales(query.lang.as_deref(), &http_req);
+    let filter = PostFilter { is_published: true, featured_only: true, ..Default::default() };
+    match all_post_with_pagination(1, limit, filter, &mut conn)
+        .and_then(|mut posts| localize_posts(&mut posts, &requested, &mut conn).map(|_| posts))
+    {
+        Ok(mut posts) => {
+            to_listing(&mut posts, &mut conn);
+            HttpResponse::Ok()
+                .content_type(APPLICATION_JSON)
+                .json(posts)
+        }
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve featured posts"})),
+    }
+}
+
+#[post("/posts/featured")]
+pub async fn reorder_featured(featured_req: web::Json<FeaturedRequest>, pool: web::Data<DBPool>) -> HttpResponse {
+    let post_ids = featured_req.into_inner().post_ids;
+    if post_ids.iter().enumerate().any(|(index, post_id)| post_ids[..index].contains(post_id)) {
+        return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "A post can only be featured once"}));
+    }
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match set_featured_posts(&post_ids, &mut conn) {
+        Ok(_) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Featured posts successfully ordered", "post_ids": post_ids})),
+        Err(Error::NotFound) => HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Featured post not found"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to order featured posts"})),
+    }
+}
+
+#[get("/posts/archive")]
+pub async fn archive(query: web::Query<ArchiveParams>, pool: web::Data<DBPool>) -> HttpResponse {
+    let cat = query.cat.clone().unwrap_or("".to_string());
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match archive_counts(cat, &mut conn) {
+        Ok(years) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(years),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve archive"})),
+    }
+}
+
+#[get("/posts/archive/{year}/{month}")]
+pub async fn archive_month(path: web::Path<(i32, u32)>, query: web::Query<PaginationParams>, http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
+    let (year, month) = path.into_inner();
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(20);
+    let cat = query.cat.clone().unwrap_or("".to_string());
+    let search = query.search.clone().unwrap_or("".to_string());
+
+    let from = match NaiveDate::from_ymd_opt(year, month, 1).and_then(|date| date.and_hms_opt(0, 0, 0)) {
+        Some(from) => from,
+        None => return HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Archive month not found"})),
+    };
+    let until = from.checked_add_months(Months::new(1)).unwrap_or(NaiveDateTime::MAX);
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    let requested = requested_locales(query.lang.as_deref(), &http_req);
+    let filter = PostFilter { cat, search, created_between: Some((from, until)), is_published: true, ..Default::default() };
+    match all_post_with_pagination(page, limit, filter, &mut conn)
+        .and_then(|mut posts| localize_posts(&mut posts, &requested, &mut conn).map(|_| posts))
+    {
+        Ok(mut posts) => {
+            to_listing(&mut posts, &mut conn);
+            HttpResponse::Ok()
+                .content_type(APPLICATION_JSON)
+                .json(posts)
+        }
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve posts"})),
+    }
+}
+
+#[get("/author/{id}/posts")]
+pub async fn author_posts(path: web::Path<Uuid>, query: web::Query<AuthorPostsParams>, http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
+    let author_id = path.into_inner();
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(20);
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    let requested = requested_locales(query.lang.as_deref(), &http_req);
+    let filter = PostFilter { author: Some(author_id), is_published: true, ..Default::default() };
+    match all_post_with_pagination(page, limit, filter, &mut conn)
+        .and_then(|mut posts| localize_posts(&mut posts, &requested, &mut conn).map(|_| posts))
+    {
+        Ok(mut posts) => {
+            to_listing(&mut posts, &mut conn);
+            HttpResponse::Ok()
+                .content_type(APPLICATION_JSON)
+                .json(posts)
+        }
         Err(_) => HttpResponse::InternalServerError()
             .content_type(APPLICATION_JSON)
             .json(serde_json::json!({"message": "Failed to retrieve posts"})),
@@ -302,14 +1094,55 @@ pub async fn active(query: web::Query<PaginationParams>, pool: web::Data<DBPool>
 }
 
 #[get("/post/{slug}")]
-pub async fn get_by_slug(path: web::Path<String>, pool: web::Data<DBPool>) -> HttpResponse {
+pub async fn get_by_slug(path: web::Path<String>, query: web::Query<LocaleParams>, http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
     let post_slug = path.into_inner();
 
     let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
-    match get_single_post_by_slug(post_slug, &mut conn) {
-        Ok(post) => HttpResponse::Ok()
-            .content_type(APPLICATION_JSON)
-            .json(post),
+    match get_single_post_by_slug(post_slug.clone(), &mut conn) {
+        Ok((mut post, slug_locale)) => {
+            if post.post.published && post.post.deleted_at.is_none() {
+                if let Err(e) = record_view(post.post.id, &http_req, &mut conn) {
+                    error!("Recording post view failed: {:?}", e);
+                }
+            }
+
+            // A translated slug picks its own locale unless `lang` asks for another one
+            let lang = query.lang.clone().or(slug_locale);
+            let requested = requested_locales(lang.as_deref(), &http_req);
+            if let Err(e) = localize_posts(std::slice::from_mut(&mut post), &requested, &mut conn) {
+                error!("Localizing post failed: {:?}", e);
+            }
+
+            match reaction_counts(post.post.id, &mut conn) {
+                Ok(counts) => post.reactions = Some(counts),
+                Err(e) => error!("Counting post reactions failed: {:?}", e),
+            }
+
+            let site = SiteSettings::load(&mut conn);
+            if let Err(e) = render_posts_html(std::slice::from_mut(&mut post), &site, &mut conn) {
+                error!("Rendering post content failed: {:?}", e);
+            }
+
+            HttpResponse::Ok()
+                .content_type(APPLICATION_JSON)
+                .json(post)
+        }
+        Err(Error::NotFound) => match get_current_slug(post_slug, &mut conn) {
+            Ok(current_slug) => {
+                let location = http_req
+                    .url_for("get_by_slug", [&current_slug])
+                    .map(|url| url.path().to_string())
+                    .unwrap_or_default();
+
+                HttpResponse::MovedPermanently()
+                    .insert_header((header::LOCATION, location))
+                    .content_type(APPLICATION_JSON)
+                    .json(serde_json::json!({"message": "Post has moved", "slug": current_slug}))
+            }
+            Err(_) => HttpResponse::NotFound()
+                .content_type(APPLICATION_JSON)
+                .json(serde_json::json!({"message": "Post not found"})),
+        },
         Err(_) => HttpResponse::InternalServerError()
             .content_type(APPLICATION_JSON)
             .json(serde_json::json!({"message": "Post not found"})),
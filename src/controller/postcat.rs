@@ -1,10 +1,14 @@
 use actix_web::{post, get, delete, web, HttpResponse};
 use chrono::{Utc, NaiveDateTime};
 use serde::{Serialize, Deserialize};
-use diesel::result::Error;
-use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, PgTextExpressionMethods, BoolExpressionMethods};
+use diesel::result::{Error, DatabaseErrorKind};
+use diesel::dsl::count;
+use diesel::sql_types::{Integer, Text};
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, PgTextExpressionMethods, BoolExpressionMethods, Connection, JoinOnDsl, QueryableByName, NullableExpressionMethods, SelectableHelper};
 
 use crate::constants::{APPLICATION_JSON, CONNECTION_POOL_ERROR};
+use crate::errors::CategoryError;
+use crate::sort::UsageSort;
 use crate::{DBPool, DBPooledConnection};
 
 use crate::models::PostCatDB;
@@ -15,7 +19,8 @@ pub struct PostCatRequest {
     pub name: String,
     pub slug: String,
     pub description: Option<String>,
-    pub published: bool
+    pub published: bool,
+    pub parent_id: Option<i32>
 }
 
 impl PostCatRequest {
@@ -29,6 +34,7 @@ impl PostCatRequest {
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
             deleted_at: None,
+            parent_id: self.parent_id,
         })
     }
 }
@@ -41,8 +47,145 @@ pub struct PaginationParams {
     pub search: Option<String>
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PublicParams {
+    pub sort: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteParams {
+    pub reassign_to: Option<i32>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeRequest {
+    pub target_id: i32
+}
+
+#[derive(QueryableByName)]
+struct CategoryId {
+    #[diesel(sql_type = Integer)]
+    id: i32
+}
+
+// A published category with how many published posts it holds
+#[derive(Debug, Serialize)]
+pub struct PublicPostCat {
+    #[serde(flatten)]
+    pub category: PostCatDB,
+    pub post_count: i64,
+    pub latest_post_at: Option<NaiveDateTime>
+}
+
+// A published category with its published subcategories
+#[derive(Debug, Serialize)]
+pub struct PostCatNode {
+    #[serde(flatten)]
+    pub category: PostCatDB,
+    pub children: Vec<PostCatNode>
+}
+
 // Class Wide Function
 
+// The category with the given slug and every category below it, empty when the slug is unknown
+pub fn category_with_descendants(cat_slug: &str, conn: &mut DBPooledConnection) -> Result<Vec<i32>, Error> {
+    let categories: Vec<CategoryId> = diesel::sql_query(
+        "WITH RECURSIVE descendants AS ( \
+            SELECT id FROM post_categories WHERE slug = $1 \
+            UNION \
+            SELECT post_categories.id FROM post_categories \
+            JOIN descendants ON post_categories.parent_id = descendants.id \
+            WHERE post_categories.deleted_at IS NULL \
+        ) SELECT id FROM descendants"
+    )
+    .bind::<Text, _>(cat_slug)
+    .load(conn)?;
+
+    Ok(categories.into_iter().map(|category| category.id).collect())
+}
+
+// Whether `pcat_id` is `new_parent_id` or one of its ancestors, locking the rows it walks through
+fn is_ancestor(pcat_id: i32, new_parent_id: i32, conn: &mut DBPooledConnection) -> Result<bool, Error> {
+    // UNION stops at rows it has seen, so an existing cycle can't loop forever
+    let ancestors = "WITH RECURSIVE ancestors AS ( \
+        SELECT id, parent_id FROM post_categories WHERE id = $1 \
+        UNION \
+        SELECT post_categories.id, post_categories.parent_id FROM post_categories \
+        JOIN ancestors ON post_categories.id = ancestors.parent_id \
+    )";
+
+    // Locking the moved category and the new parent chain in id order keeps two crossing moves from both passing
+    diesel::sql_query(format!("{} SELECT id FROM post_categories WHERE id = $2 OR id IN (SELECT id FROM ancestors) ORDER BY id FOR UPDATE", ancestors))
+        .bind::<Integer, _>(new_parent_id)
+        .bind::<Integer, _>(pcat_id)
+        .load::<CategoryId>(conn)?;
+
+    // Walked again once the locks are held, a move committed meanwhile is seen now
+    let ancestor_ids: Vec<CategoryId> = diesel::sql_query(format!("{} SELECT id FROM ancestors", ancestors))
+        .bind::<Integer, _>(new_parent_id)
+        .load(conn)?;
+
+    Ok(ancestor_ids.iter().any(|ancestor| ancestor.id == pcat_id))
+}
+
+// Nests the categories below `parent`, categories whose parent isn't in the list are left out
+fn build_tree(parent: Option<i32>, categories: &mut Vec<PostCatDB>) -> Vec<PostCatNode> {
+    let mut nodes = Vec::new();
+    while let Some(index) = categories.iter().position(|category| category.parent_id == parent) {
+        nodes.push(PostCatNode {
+            category: categories.remove(index),
+            children: Vec::new()
+        });
+    }
+
+    for node in nodes.iter_mut() {
+        node.children = build_tree(Some(node.category.id), categories);
+    }
+
+    nodes
+}
+
+fn public_categories(sort: UsageSort, conn: &mut DBPooledConnection) -> Result<Vec<PublicPostCat>, Error> {
+    use crate::schema::post_categories::dsl::*;
+    use crate::schema::posts::dsl::{posts, id as post_id, category_id, created_at as post_created_at, published as post_published, deleted_at as post_deleted_at};
+
+    let mut query = post_categories
+        .left_join(posts.on(
+            category_id.eq(id)
+                .and(post_published.eq(true))
+                .and(post_deleted_at.is_null())
+        ))
+        .filter(published.eq(true))
+        .filter(deleted_at.is_null())
+        .group_by(id)
+        .select((PostCatDB::as_select(), count(post_id.nullable()), diesel::dsl::max(post_created_at.nullable())))
+        .into_boxed();
+
+    // Busiest categories first, the name keeps ties in a stable order
+    query = match sort {
+        UsageSort::Name => query.order_by(name.asc()),
+        UsageSort::Count => query.order_by((count(post_id.nullable()).desc(), name.asc())),
+    };
+
+    Ok(query
+        .load::<(PostCatDB, i64, Option<NaiveDateTime>)>(conn)?
+        .into_iter()
+        .map(|(category, post_count, latest_post_at)| PublicPostCat { category, post_count, latest_post_at })
+        .collect())
+}
+
+fn category_tree(conn: &mut DBPooledConnection) -> Result<Vec<PostCatNode>, Error> {
+    use crate::schema::post_categories::dsl::*;
+
+    let mut categories = post_categories
+        .filter(published.eq(true))
+        .filter(deleted_at.is_null())
+        .order_by(name.asc())
+        .load::<PostCatDB>(conn)?;
+
+    Ok(build_tree(None, &mut categories))
+}
+
 fn create_pcat(post_category: PostCatDB, conn: &mut DBPooledConnection) -> Result<PostCatDB, Error> {
     use crate::schema::post_categories::dsl::*;
     diesel::insert_into(post_categories)
@@ -51,6 +194,7 @@ fn create_pcat(post_category: PostCatDB, conn: &mut DBPooledConnection) -> Resul
             slug.eq(&post_category.slug),
             description.eq(&post_category.description),
             published.eq(post_category.published),
+            parent_id.eq(post_category.parent_id),
             created_at.eq(post_category.created_at),
             updated_at.eq(post_category.updated_at),
             deleted_at.eq(post_category.deleted_at),
@@ -58,17 +202,116 @@ fn create_pcat(post_category: PostCatDB, conn: &mut DBPooledConnection) -> Resul
         .get_result(conn)
 }
 
-fn update_pcat(post_category: PostCatDB, pcat_id: i32, conn: &mut DBPooledConnection) -> Result<PostCatDB, Error> {
+fn update_pcat(post_category: PostCatDB, pcat_id: i32, conn: &mut DBPooledConnection) -> Result<PostCatDB, CategoryError> {
     use crate::schema::post_categories::dsl::*;
-    diesel::update(post_categories.filter(id.eq(pcat_id)))
-        .set((
-            name.eq(post_category.name),
-            slug.eq(post_category.slug),
-            description.eq(post_category.description),
-            published.eq(post_category.published),
-            updated_at.eq(Utc::now().naive_utc()),
-        ))
-        .get_result(conn)
+
+    conn.transaction(|conn| {
+        if let Some(new_parent_id) = post_category.parent_id {
+            if is_ancestor(pcat_id, new_parent_id, conn)? {
+                return Err(CategoryError::Cycle(pcat_id));
+            }
+        }
+
+        let updated = diesel::update(post_categories.filter(id.eq(pcat_id)))
+            .set((
+                name.eq(post_category.name),
+                slug.eq(post_category.slug),
+                description.eq(post_category.description),
+                published.eq(post_category.published),
+                parent_id.eq(post_category.parent_id),
+                updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .get_result(conn)?;
+
+        Ok(updated)
+    })
+}
+
+// Soft deletes a category after moving its posts to the target, subcategories move up to its parent
+fn retire_category(source_id: i32, target_id: Option<i32>, conn: &mut DBPooledConnection) -> Result<usize, CategoryError> {
+    use crate::schema::post_categories::dsl::*;
+    use crate::schema::posts::dsl::{posts, category_id};
+
+    conn.transaction(|conn| {
+        let source_parent_id = post_categories
+            .filter(id.eq(source_id))
+            .filter(deleted_at.is_null())
+            .select(parent_id)
+            .for_update()
+            .first::<Option<i32>>(conn)?;
+
+        let post_count = posts
+            .filter(category_id.eq(source_id))
+            .count()
+            .get_result::<i64>(conn)?;
+
+        let moved = match target_id {
+            Some(target_id) => {
+                let target_exists = post_categories
+                    .filter(id.eq(target_id))
+                    .filter(deleted_at.is_null())
+                    .count()
+                    .get_result::<i64>(conn)? > 0;
+                if target_id == source_id || !target_exists {
+                    return Err(CategoryError::InvalidTarget(target_id));
+                }
+
+                diesel::update(posts.filter(category_id.eq(source_id)))
+                    .set(category_id.eq(target_id))
+                    .execute(conn)?
+            }
+            // Deleting would hide every post still filed under the category
+            None if post_count > 0 => return Err(CategoryError::HasPosts(post_count)),
+            None => 0,
+        };
+
+        diesel::update(post_categories.filter(parent_id.eq(source_id)))
+            .set(parent_id.eq(source_parent_id))
+            .execute(conn)?;
+
+        diesel::update(post_categories.filter(id.eq(source_id)))
+            .set(deleted_at.eq(Some(Utc::now().naive_utc())))
+            .execute(conn)?;
+
+        Ok(moved)
+    })
+}
+
+fn retire_response(retired: Result<usize, CategoryError>, message: &str) -> HttpResponse {
+    match retired {
+        Ok(moved) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": message, "moved_posts": moved})),
+        Err(CategoryError::DatabaseError(Error::NotFound)) => HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Post category not found"})),
+        Err(e @ CategoryError::HasPosts(_)) => HttpResponse::Conflict()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": e.to_string()})),
+        Err(e @ CategoryError::InvalidTarget(_)) => HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": e.to_string()})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to delete post category"})),
+    }
+}
+
+fn save_error_response(action: &str, e: CategoryError) -> HttpResponse {
+    match e {
+        CategoryError::DatabaseError(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => HttpResponse::Conflict()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error {} post category: slug is already taken", action)),
+        CategoryError::DatabaseError(Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) => HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error {} post category: parent category not found", action)),
+        CategoryError::Cycle(_) => HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error {} post category: {}", action, e)),
+        _ => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error {} post category: {}", action, e)),
+    }
 }
 
 fn all_postcat_with_pagination(page: i32, limit: i32, search: String, is_published: bool, conn: &mut DBPooledConnection) -> Result<Vec<PostCatDB>, Error> {
@@ -107,9 +350,7 @@ pub async fn create(postcat_req: web::Json<PostCatRequest>, pool: web::Data<DBPo
                 Ok(inserted_postcat) => HttpResponse::Created()
                     .content_type(APPLICATION_JSON)
                     .json(inserted_postcat),
-                Err(e) => HttpResponse::InternalServerError()
-                    .content_type(APPLICATION_JSON)
-                    .json(format!("Error inserting post category: {}", e)),
+                Err(e) => save_error_response("inserting", e.into()),
             }
         }
         Err(e) => HttpResponse::BadRequest()
@@ -128,9 +369,7 @@ pub async fn update(path: web::Path<i32>, postcat_req: web::Json<PostCatRequest>
                 Ok(updated_postcat) => HttpResponse::Created()
                     .content_type(APPLICATION_JSON)
                     .json(updated_postcat),
-                Err(e) => HttpResponse::InternalServerError()
-                    .content_type(APPLICATION_JSON)
-                    .json(format!("Error updating post category: {}", e)),
+                Err(e) => save_error_response("updating", e),
             }
         }
         Err(e) => HttpResponse::BadRequest()
@@ -157,24 +396,19 @@ pub async fn get(path: web::Path<i32>, pool: web::Data<DBPool>) -> HttpResponse
 }
 
 #[delete("/post-category/{id}")]
-pub async fn delete(path: web::Path<i32>, pool: web::Data<DBPool>) -> HttpResponse {
+pub async fn delete(path: web::Path<i32>, query: web::Query<DeleteParams>, pool: web::Data<DBPool>) -> HttpResponse {
     let pcat_id = path.into_inner();
-    let current_time = Utc::now().naive_utc();
 
-    use crate::schema::post_categories::dsl::*;
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    retire_response(retire_category(pcat_id, query.reassign_to, &mut conn), "Post category successfully deleted")
+}
+
+#[post("/post-category/{id}/merge")]
+pub async fn merge(path: web::Path<i32>, merge_req: web::Json<MergeRequest>, pool: web::Data<DBPool>) -> HttpResponse {
+    let pcat_id = path.into_inner();
 
     let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
-    match diesel::update(post_categories.filter(id.eq(pcat_id)))
-        .set(deleted_at.eq(Some(current_time)))
-        .execute(&mut conn)
-    {
-        Ok(_) => HttpResponse::Ok()
-            .content_type(APPLICATION_JSON)
-            .json(serde_json::json!({"message": "Post category successfully deleted"})),
-        Err(_) => HttpResponse::InternalServerError()
-            .content_type(APPLICATION_JSON)
-            .json(serde_json::json!({"message": "Failed to delete post category"})),
-    }
+    retire_response(retire_category(pcat_id, Some(merge_req.target_id), &mut conn), "Post category successfully merged")
 }
 
 #[post("/post-category/{id}/restore")]
@@ -229,4 +463,37 @@ pub async fn active(query: web::Query<PaginationParams>, pool: web::Data<DBPool>
             .content_type(APPLICATION_JSON)
             .json(serde_json::json!({"message": "Failed to retrieve post categories"})),
     }
-}
\ No newline at end of file
+}
+
+#[get("/post-categories/tree")]
+pub async fn tree(pool: web::Data<DBPool>) -> HttpResponse {
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match category_tree(&mut conn) {
+        Ok(nodes) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(nodes),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve post category tree"})),
+    }
+}
+
+#[get("/post-categories")]
+pub async fn public(query: web::Query<PublicParams>, pool: web::Data<DBPool>) -> HttpResponse {
+    let sort = match UsageSort::parse(query.sort.as_deref()) {
+        Ok(sort) => sort,
+        Err(message) => return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": message})),
+    };
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match public_categories(sort, &mut conn) {
+        Ok(postcats) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(postcats),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve post categories"})),
+    }
+}
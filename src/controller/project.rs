@@ -2,13 +2,18 @@ use actix_web::{post, get, delete, web, HttpResponse};
 use chrono::{Utc, NaiveDateTime};
 use serde::{Serialize, Deserialize};
 use diesel::result::Error;
-use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, JoinOnDsl, Queryable, Table, PgTextExpressionMethods, BoolExpressionMethods};
+use diesel::dsl::count_star;
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, Queryable, PgTextExpressionMethods, BoolExpressionMethods, Connection, BelongingToDsl, GroupedBy, SelectableHelper};
 
 use crate::constants::{APPLICATION_JSON, CONNECTION_POOL_ERROR};
 use crate::{DBPool, DBPooledConnection};
+use crate::errors::{ReorderError, TechFilterError};
+use crate::ordering::{self, Ordered};
 
 use crate::models::ProjectDB;
 use crate::models::TechDB;
+use crate::models::ProjectTechDB;
+use crate::controller::tech::resolve_tech_ids;
 
 // Project Request Struct
 #[derive(Debug, Deserialize, Serialize)]
@@ -20,8 +25,7 @@ pub struct ProjectRequest {
     pub demo: Option<String>,
     pub relevant: bool,
     pub published: bool,
-    pub tech_ids: Vec<i32>,
-    pub order: i32
+    pub tech_ids: Vec<i32>
 }
 
 #[derive(Queryable, Debug, Serialize)]
@@ -31,86 +35,128 @@ pub struct ProjectTechJoin {
     pub techs: Vec<TechDB>
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ReorderRequest {
+    pub ids: Vec<i32>
+}
+
 // Pagination Request Struct
 #[derive(Debug, Deserialize)]
 pub struct PaginationParams {
     pub page: Option<i32>,
     pub limit: Option<i32>,
     pub rlv: Option<bool>,
-    pub search: Option<String>
+    pub search: Option<String>,
+    pub tech: Option<String>,
+    pub techs: Option<String>,
+    #[serde(rename = "match")]
+    pub tech_match: Option<String>
+}
+
+// Projects need `required` of the given techs, 1 for any-of and all of them for all-of
+#[derive(Debug)]
+pub struct TechFilter {
+    pub tech_ids: Vec<i32>,
+    pub required: usize
 }
 
 // Class Wide Function
 
-fn create_project(project: ProjectRequest, conn: &mut DBPooledConnection) -> Result<ProjectTechJoin, Error> {
-    use crate::schema::projects::dsl::*;
-    use crate::schema::projects_techs::dsl::{projects_techs, project_id as pt_project_id, tech_id as pt_tech_id};
+// Links the techs that exist, unknown ids are skipped instead of failing the save
+fn set_project_techs(tech_project_id: i32, tech_ids: &[i32], conn: &mut DBPooledConnection) -> Result<usize, Error> {
+    use crate::schema::projects_techs::dsl::{projects_techs, project_id, tech_id};
     use crate::schema::techs::dsl::{techs, id as techs_id};
 
-    let next_order =  get_next_order(conn)?;
+    let known_ids = techs
+        .filter(techs_id.eq_any(tech_ids))
+        .select(techs_id)
+        .load::<i32>(conn)?;
+    let rows: Vec<ProjectTechDB> = known_ids
+        .into_iter()
+        .map(|known_id| ProjectTechDB { project_id: tech_project_id, tech_id: known_id })
+        .collect();
+
+    diesel::insert_into(projects_techs)
+        .values(&rows)
+        .on_conflict((project_id, tech_id))
+        .do_nothing()
+        .execute(conn)
+}
 
-    let project_db: ProjectDB = ProjectDB {
-        id: 1,
-        title: project.title.clone(),
-        content: project.content.clone(),
-        source: Some(project.source.clone().unwrap_or("".to_string())),
-        url: Some(project.url.clone().unwrap_or("".to_string())),
-        demo: Some(project.demo.clone().unwrap_or("".to_string())),
-        relevant: project.relevant,
-        created_at: Utc::now().naive_utc(),
-        updated_at: Utc::now().naive_utc(),
-        deleted_at: None,
-        published: project.published,
-        order: next_order
-    };
-    
-    let inserted_project: ProjectDB = diesel::insert_into(projects)
-        .values((
-            title.eq(project_db.title),
-            content.eq(project_db.content),
-            source.eq(project_db.source),
-            url.eq(project_db.url),
-            demo.eq(project_db.demo),
-            relevant.eq(project_db.relevant),
-            created_at.eq(project_db.created_at),
-            updated_at.eq(project_db.updated_at),
-            deleted_at.eq(project_db.deleted_at),
-            published.eq(project_db.published),
-            order.eq(project_db.order),
-        ))
-        .get_result(conn)?;
+// Loads the techs of a whole page of projects in one query, keeping the page order
+fn attach_techs(projects_list: Vec<ProjectDB>, conn: &mut DBPooledConnection) -> Result<Vec<ProjectTechJoin>, Error> {
+    use crate::schema::techs::dsl::techs;
+
+    let tech_rows = ProjectTechDB::belonging_to(&projects_list)
+        .inner_join(techs)
+        .select((ProjectTechDB::as_select(), TechDB::as_select()))
+        .load::<(ProjectTechDB, TechDB)>(conn)?;
+
+    let result = tech_rows
+        .grouped_by(&projects_list)
+        .into_iter()
+        .zip(projects_list)
+        .map(|(project_techs, project)| ProjectTechJoin {
+            project,
+            techs: project_techs.into_iter().map(|(_, tech)| tech).collect()
+        })
+        .collect();
 
-    for tech_id_item in project.tech_ids {
-        let _ = diesel::insert_into(projects_techs)
+    Ok(result)
+}
+
+fn project_with_techs(project: ProjectDB, conn: &mut DBPooledConnection) -> Result<ProjectTechJoin, Error> {
+    attach_techs(vec![project], conn)?
+        .pop()
+        .ok_or(Error::NotFound)
+}
+
+fn create_project(project: ProjectRequest, conn: &mut DBPooledConnection) -> Result<ProjectTechJoin, Error> {
+    use crate::schema::projects::dsl::*;
+
+    conn.transaction(|conn| {
+        ordering::lock_ordering(Ordered::Projects, conn)?;
+        let next_order = get_next_order(conn)?;
+
+        let project_db: ProjectDB = ProjectDB {
+            id: 1,
+            title: project.title.clone(),
+            content: project.content.clone(),
+            source: Some(project.source.clone().unwrap_or("".to_string())),
+            url: Some(project.url.clone().unwrap_or("".to_string())),
+            demo: Some(project.demo.clone().unwrap_or("".to_string())),
+            relevant: project.relevant,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+            deleted_at: None,
+            published: project.published,
+            order: next_order
+        };
+    
+        let inserted_project: ProjectDB = diesel::insert_into(projects)
             .values((
-                pt_project_id.eq(inserted_project.id),
-                pt_tech_id.eq(tech_id_item),
+                title.eq(project_db.title),
+                content.eq(project_db.content),
+                source.eq(project_db.source),
+                url.eq(project_db.url),
+                demo.eq(project_db.demo),
+                relevant.eq(project_db.relevant),
+                created_at.eq(project_db.created_at),
+                updated_at.eq(project_db.updated_at),
+                deleted_at.eq(project_db.deleted_at),
+                published.eq(project_db.published),
+                order.eq(project_db.order),
             ))
-            .execute(conn);
-    }
-    
-    let project = projects
-        .find(inserted_project.id)
-        .first::<ProjectDB>(conn)?;
+            .get_result(conn)?;
 
-    let tech_list = projects_techs
-        .filter(pt_project_id.eq(inserted_project.id))
-        .inner_join(
-            techs.on(pt_tech_id.eq(techs_id))
-        )
-        .select(techs::all_columns())
-        .load::<TechDB>(conn)?;
-
-    Ok(ProjectTechJoin {
-        project,
-        techs: tech_list
+        set_project_techs(inserted_project.id, &project.tech_ids, conn)?;
+
+        project_with_techs(inserted_project, conn)
     })
 }
 
 fn update_project(project: ProjectRequest, project_id: i32, conn: &mut DBPooledConnection) -> Result<ProjectTechJoin, Error> {
     use crate::schema::projects::dsl::*;
-    use crate::schema::projects_techs::dsl::{projects_techs, project_id as pt_project_id, tech_id as pt_tech_id};
-    use crate::schema::techs::dsl::{techs, id as techs_id};
 
     let project_db: ProjectDB = ProjectDB {
         id: 1,
@@ -124,10 +170,11 @@ fn update_project(project: ProjectRequest, project_id: i32, conn: &mut DBPooledC
         updated_at: Utc::now().naive_utc(),
         deleted_at: None,
         published: project.published,
-        order: project.order.clone(),
+        // Orders are handed out on create and only change through /projects/reorder
+        order: 0,
     };
     
-    let _: ProjectDB = diesel::update(projects.filter(id.eq(project_id)))
+    let updated_project: ProjectDB = diesel::update(projects.filter(id.eq(project_id)))
         .set((
             title.eq(project_db.title),
             content.eq(project_db.content),
@@ -137,44 +184,54 @@ fn update_project(project: ProjectRequest, project_id: i32, conn: &mut DBPooledC
             relevant.eq(project_db.relevant),
             updated_at.eq(Utc::now().naive_utc()),
             published.eq(project_db.published),
-            order.eq(project_db.order)
         ))
         .get_result(conn)?;
 
-    diesel::delete(projects_techs.filter(pt_project_id.eq(project_id)))
+    diesel::delete(ProjectTechDB::belonging_to(&updated_project))
         .execute(conn)?;
+    set_project_techs(project_id, &project.tech_ids, conn)?;
 
-    for tech_id_item in project.tech_ids {
-        let _ = diesel::insert_into(projects_techs)
-            .values((
-                pt_project_id.eq(project_id),
-                pt_tech_id.eq(tech_id_item),
-            ))
-            .execute(conn);
+    project_with_techs(updated_project, conn)
+}
+
+// Reads `tech` and `techs` (ids or slugs) with `match` any or all, None when neither is given
+fn tech_filter(params: &PaginationParams, conn: &mut DBPooledConnection) -> Result<Option<TechFilter>, TechFilterError> {
+    let match_all = match params.tech_match.as_deref() {
+        None | Some("any") => false,
+        Some("all") => true,
+        Some(other) => return Err(TechFilterError::InvalidMatch(other.to_string())),
+    };
+
+    let mut identifiers: Vec<String> = params.tech
+        .iter()
+        .chain(params.techs.iter())
+        .flat_map(|list| list.split(','))
+        .map(|identifier| identifier.trim().to_lowercase())
+        .filter(|identifier| !identifier.is_empty())
+        .collect();
+    identifiers.sort();
+    identifiers.dedup();
+    if identifiers.is_empty() {
+        return Ok(None);
     }
-    
-    let project = projects
-        .find(project_id)
-        .first::<ProjectDB>(conn)?;
 
-    let tech_list = projects_techs
-        .filter(pt_project_id.eq(project_id))
-        .inner_join(
-            techs.on(pt_tech_id.eq(techs_id))
-        )
-        .select(techs::all_columns())
-        .load::<TechDB>(conn)?;
-
-    Ok(ProjectTechJoin {
-        project,
-        techs: tech_list
-    })
+    let resolved = resolve_tech_ids(&identifiers, conn)?;
+    if let Some(unknown) = identifiers.iter().find(|identifier| !resolved.contains_key(*identifier)) {
+        return Err(TechFilterError::UnknownTech(unknown.clone()));
+    }
+
+    // An id and a slug may name the same tech, all-of counts it once
+    let mut tech_ids: Vec<i32> = resolved.into_values().collect();
+    tech_ids.sort();
+    tech_ids.dedup();
+    let required = if match_all { tech_ids.len() } else { 1 };
+
+    Ok(Some(TechFilter { tech_ids, required }))
 }
 
-fn all_project_with_pagination(page: i32, limit: i32, rlv: bool, search: String, activated: bool, conn: &mut DBPooledConnection) -> Result<Vec<ProjectTechJoin>, Error> {
+fn all_project_with_pagination(page: i32, limit: i32, rlv: bool, search: String, activated: bool, techs_filter: Option<TechFilter>, conn: &mut DBPooledConnection) -> Result<Vec<ProjectTechJoin>, Error> {
     use crate::schema::projects::dsl::*;
-    use crate::schema::projects_techs::dsl::{projects_techs, project_id as pt_project_id, tech_id as pt_tech_id};
-    use crate::schema::techs::dsl::{techs, id as techs_id};
+    use crate::schema::projects_techs::dsl::{projects_techs, project_id, tech_id};
 
     let mut query = projects
         .filter(deleted_at.is_null())
@@ -197,50 +254,29 @@ fn all_project_with_pagination(page: i32, limit: i32, rlv: bool, search: String,
     if activated {
         query = query.filter(published.eq(activated));
     }
-        
-    let projects_list = query.load::<ProjectDB>(conn)?;
 
-    let mut result = Vec::new();
-
-    for project in projects_list {
-        let tech_list = projects_techs
-            .filter(pt_project_id.eq(project.id))
-            .inner_join(
-                techs.on(pt_tech_id.eq(techs_id))
-            )
-            .select(techs::all_columns())
-            .load::<TechDB>(conn)?;
-
-        result.push(ProjectTechJoin {
-            project,
-            techs: tech_list,
-        })
+    if let Some(techs_filter) = techs_filter {
+        let matching_ids = projects_techs
+            .filter(tech_id.eq_any(techs_filter.tech_ids))
+            .group_by(project_id)
+            .having(count_star().ge(techs_filter.required as i64))
+            .select(project_id);
+        query = query.filter(id.eq_any(matching_ids));
     }
+        
+    let projects_list = query.load::<ProjectDB>(conn)?;
 
-    Ok(result)
+    attach_techs(projects_list, conn)
 }
 
 fn get_single_project(project_id: i32, conn: &mut DBPooledConnection) -> Result<ProjectTechJoin, Error> {
     use crate::schema::projects::dsl::*;
-    use crate::schema::projects_techs::dsl::{projects_techs, project_id as pt_project_id, tech_id as pt_tech_id};
-    use crate::schema::techs::dsl::{techs, id as techs_id};
 
     let project = projects
         .find(project_id)
         .first::<ProjectDB>(conn)?;
 
-    let tech_list = projects_techs
-        .filter(pt_project_id.eq(project_id))
-        .inner_join(
-            techs.on(pt_tech_id.eq(techs_id))
-        )
-        .select(techs::all_columns())
-        .load::<TechDB>(conn)?;
-
-    Ok(ProjectTechJoin {
-        project,
-        techs: tech_list
-    })
+    project_with_techs(project, conn)
 }
 
 fn get_next_order(conn: &mut DBPooledConnection) -> Result<i32, Error> {
@@ -349,10 +385,15 @@ pub async fn all(query: web::Query<PaginationParams>, pool: web::Data<DBPool>) -
     let search = query.search.clone().unwrap_or("".to_string());
 
     let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
-    match all_project_with_pagination(page, limit, false, search, false, &mut conn) {
+    match tech_filter(&query, &mut conn)
+        .and_then(|techs_filter| Ok(all_project_with_pagination(page, limit, false, search, false, techs_filter, &mut conn)?))
+    {
         Ok(projects) => HttpResponse::Ok()
             .content_type(APPLICATION_JSON)
             .json(projects),
+        Err(e @ (TechFilterError::UnknownTech(_) | TechFilterError::InvalidMatch(_))) => HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": e.to_string()})),
         Err(_) => HttpResponse::InternalServerError()
             .content_type(APPLICATION_JSON)
             .json(serde_json::json!({"message": "Failed to retrieve projects"})),
@@ -367,12 +408,106 @@ pub async fn active(query: web::Query<PaginationParams>, pool: web::Data<DBPool>
     let search = query.search.clone().unwrap_or("".to_string());
 
     let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
-    match all_project_with_pagination(page, limit, rlv, search, true, &mut conn) {
+    match tech_filter(&query, &mut conn)
+        .and_then(|techs_filter| Ok(all_project_with_pagination(page, limit, rlv, search, true, techs_filter, &mut conn)?))
+    {
         Ok(projects) => HttpResponse::Ok()
             .content_type(APPLICATION_JSON)
             .json(projects),
+        Err(e @ (TechFilterError::UnknownTech(_) | TechFilterError::InvalidMatch(_))) => HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": e.to_string()})),
         Err(_) => HttpResponse::InternalServerError()
             .content_type(APPLICATION_JSON)
             .json(serde_json::json!({"message": "Failed to retrieve projects"})),
     }
-}
\ No newline at end of file
+}
+
+#[post("/projects/reorder")]
+pub async fn reorder(reorder_req: web::Json<ReorderRequest>, pool: web::Data<DBPool>) -> HttpResponse {
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match ordering::reorder(Ordered::Projects, &reorder_req.ids, &mut conn) {
+        Ok(reordered) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Projects successfully reordered", "reordered": reordered})),
+        Err(ReorderError::Mismatch) => HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "ids must list every project exactly once"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to reorder projects"})),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::connection::InstrumentationEvent;
+    use diesel::r2d2::{ConnectionManager, Pool};
+    use diesel::PgConnection;
+    use std::env;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+    fn seed_projects(count: usize, conn: &mut DBPooledConnection) -> Vec<i32> {
+        use crate::schema::techs::dsl::*;
+
+        let tech_ids = diesel::insert_into(techs)
+            .values(vec![
+                (title.eq("Query Count Rust"), slug.eq("query-count-rust"), created_at.eq(Utc::now().naive_utc()), updated_at.eq(Utc::now().naive_utc())),
+                (title.eq("Query Count Diesel"), slug.eq("query-count-diesel"), created_at.eq(Utc::now().naive_utc()), updated_at.eq(Utc::now().naive_utc())),
+            ])
+            .returning(id)
+            .get_results::<i32>(conn)
+            .expect("Failed to insert techs");
+
+        for index in 0..count {
+            create_project(ProjectRequest {
+                title: format!("Query Count {}", index),
+                content: "".to_string(),
+                source: None,
+                url: None,
+                demo: None,
+                relevant: false,
+                published: true,
+                tech_ids: tech_ids.clone()
+            }, conn).expect("Failed to create project");
+        }
+
+        tech_ids
+    }
+
+    // Runs a listing and returns how many statements it sent
+    fn count_queries(limit: i32, queries: &AtomicUsize, conn: &mut DBPooledConnection) -> usize {
+        queries.store(0, AtomicOrdering::SeqCst);
+        let listed = all_project_with_pagination(1, limit, false, "".to_string(), false, None, conn).expect("Failed to list projects");
+
+        assert_eq!(listed.len(), limit as usize);
+        assert!(listed.iter().all(|joined| joined.techs.len() == 2));
+        queries.load(AtomicOrdering::SeqCst)
+    }
+
+    #[test]
+    #[ignore = "needs a migrated database in DATABASE_URL"]
+    fn project_page_loads_techs_with_a_fixed_number_of_queries() {
+        let _ = dotenvy::dotenv();
+        let manager = ConnectionManager::<PgConnection>::new(env::var("DATABASE_URL").expect("DATABASE_URL"));
+        let pool = Pool::builder().max_size(1).build(manager).expect("Failed to create pool");
+        let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+        conn.begin_test_transaction().expect("Failed to begin test transaction");
+
+        seed_projects(10, &mut conn);
+
+        let queries = Arc::new(AtomicUsize::new(0));
+        let counter = queries.clone();
+        conn.set_instrumentation(move |event: InstrumentationEvent<'_>| {
+            if let InstrumentationEvent::FinishQuery { .. } = event {
+                counter.fetch_add(1, AtomicOrdering::SeqCst);
+            }
+        });
+
+        let single = count_queries(1, &queries, &mut conn);
+        assert!(single > 0);
+        assert_eq!(single, count_queries(10, &queries, &mut conn));
+    }
+}
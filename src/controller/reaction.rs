@@ -0,0 +1,177 @@
+use actix_web::{post, get, delete, web, HttpRequest, HttpResponse};
+use chrono::{Utc, NaiveDateTime};
+use serde::{Serialize, Deserialize};
+use diesel::result::Error;
+use diesel::dsl::count_star;
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods};
+use std::collections::BTreeMap;
+
+use crate::constants::{APPLICATION_JSON, CONNECTION_POOL_ERROR};
+use crate::{DBPool, DBPooledConnection};
+
+use crate::controller::comment::get_published_post_id;
+use crate::controller::view::visitor_hash;
+
+const REACTIONS: [&str; 3] = ["like", "clap", "insightful"];
+
+// Reaction Request Struct
+#[derive(Debug, Deserialize)]
+pub struct ReactionRequest {
+    pub reaction: String
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReactionBreakdown {
+    pub reaction: String,
+    pub count: i64,
+    pub last_reacted_at: Option<NaiveDateTime>
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostReactionStats {
+    pub post_id: i32,
+    pub total: i64,
+    pub reactions: Vec<ReactionBreakdown>
+}
+
+// Class Wide Function
+
+fn validate_reaction(requested: &str) -> Result<String, String> {
+    let requested = requested.trim().to_lowercase();
+    if REACTIONS.contains(&requested.as_str()) {
+        Ok(requested)
+    } else {
+        Err(format!("Reaction must be one of: {}", REACTIONS.join(", ")))
+    }
+}
+
+// Every known reaction with its count, so clients don't have to fill in zeroes
+pub fn reaction_counts(counted_post_id: i32, conn: &mut DBPooledConnection) -> Result<BTreeMap<String, i64>, Error> {
+    use crate::schema::post_reactions::dsl::*;
+
+    let mut counts: BTreeMap<String, i64> = REACTIONS.iter().map(|known| (known.to_string(), 0)).collect();
+    let rows = post_reactions
+        .filter(post_id.eq(counted_post_id))
+        .group_by(reaction)
+        .select((reaction, count_star()))
+        .load::<(String, i64)>(conn)?;
+    counts.extend(rows);
+
+    Ok(counts)
+}
+
+fn add_reaction(reacted_post_id: i32, new_reaction: &str, visitor: &str, conn: &mut DBPooledConnection) -> Result<usize, Error> {
+    use crate::schema::post_reactions::dsl::*;
+
+    diesel::insert_into(post_reactions)
+        .values((
+            post_id.eq(reacted_post_id),
+            reaction.eq(new_reaction),
+            visitor_hash.eq(visitor),
+            created_at.eq(Utc::now().naive_utc()),
+        ))
+        .on_conflict_do_nothing()
+        .execute(conn)
+}
+
+fn remove_reaction(reacted_post_id: i32, old_reaction: &str, visitor: &str, conn: &mut DBPooledConnection) -> Result<usize, Error> {
+    use crate::schema::post_reactions::dsl::*;
+
+    diesel::delete(
+        post_reactions
+            .filter(post_id.eq(reacted_post_id))
+            .filter(reaction.eq(old_reaction))
+            .filter(visitor_hash.eq(visitor))
+    )
+    .execute(conn)
+}
+
+fn reaction_stats(stats_post_id: i32, conn: &mut DBPooledConnection) -> Result<PostReactionStats, Error> {
+    use crate::schema::post_reactions::dsl::*;
+
+    let rows = post_reactions
+        .filter(post_id.eq(stats_post_id))
+        .group_by(reaction)
+        .select((reaction, count_star(), diesel::dsl::max(created_at)))
+        .load::<(String, i64, Option<NaiveDateTime>)>(conn)?;
+
+    let reactions: Vec<ReactionBreakdown> = REACTIONS
+        .iter()
+        .map(|known| {
+            let row = rows.iter().find(|(row_reaction, _, _)| row_reaction == known);
+            ReactionBreakdown {
+                reaction: known.to_string(),
+                count: row.map(|(_, row_count, _)| *row_count).unwrap_or(0),
+                last_reacted_at: row.and_then(|(_, _, last)| *last),
+            }
+        })
+        .collect();
+
+    Ok(PostReactionStats {
+        post_id: stats_post_id,
+        total: reactions.iter().map(|breakdown| breakdown.count).sum(),
+        reactions
+    })
+}
+
+fn reaction_response(post_slug: String, requested: &str, http_req: HttpRequest, pool: web::Data<DBPool>, adding: bool) -> HttpResponse {
+    let requested = match validate_reaction(requested) {
+        Ok(requested) => requested,
+        Err(e) => return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": e})),
+    };
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    let reacted_post_id = match get_published_post_id(&post_slug, &mut conn) {
+        Ok(reacted_post_id) => reacted_post_id,
+        Err(_) => return HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Post not found"})),
+    };
+
+    // One reaction of each kind per visitor and post, repeats are silently ignored
+    let visitor = visitor_hash(&http_req, "reaction");
+    let saved = if adding {
+        add_reaction(reacted_post_id, &requested, &visitor, &mut conn)
+    } else {
+        remove_reaction(reacted_post_id, &requested, &visitor, &mut conn)
+    };
+
+    match saved.and_then(|_| reaction_counts(reacted_post_id, &mut conn)) {
+        Ok(counts) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"reacted": adding, "reaction": requested, "reactions": counts})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to save reaction"})),
+    }
+}
+
+// Routing
+
+#[post("/post/{slug}/reaction")]
+pub async fn add(path: web::Path<String>, reaction_req: web::Json<ReactionRequest>, http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
+    reaction_response(path.into_inner(), &reaction_req.reaction, http_req, pool, true)
+}
+
+#[delete("/post/{slug}/reaction/{reaction}")]
+pub async fn remove(path: web::Path<(String, String)>, http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
+    let (post_slug, requested) = path.into_inner();
+    reaction_response(post_slug, &requested, http_req, pool, false)
+}
+
+#[get("/post/{id}/reactions")]
+pub async fn stats(path: web::Path<i32>, pool: web::Data<DBPool>) -> HttpResponse {
+    let stats_post_id = path.into_inner();
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match reaction_stats(stats_post_id, &mut conn) {
+        Ok(post_stats) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(post_stats),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve reactions"})),
+    }
+}
@@ -0,0 +1,476 @@
+use actix_web::{post, get, delete, web, HttpResponse};
+use chrono::{Utc, NaiveDateTime};
+use serde::{Serialize, Deserialize};
+use diesel::result::{Error, DatabaseErrorKind};
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, OptionalExtension, PgTextExpressionMethods, BoolExpressionMethods, SelectableHelper, Connection};
+use std::collections::HashSet;
+
+use crate::constants::{APPLICATION_JSON, CONNECTION_POOL_ERROR};
+use crate::{DBPool, DBPooledConnection};
+use crate::errors::SeriesError;
+
+use crate::controller::post::{JoinedPost, get_posts_by_ids, to_listing};
+use crate::models::SeriesDB;
+use crate::slug::slugify;
+
+// Series Request Struct
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SeriesRequest {
+    pub name: String,
+    pub slug: Option<String>,
+    pub description: Option<String>,
+    pub published: bool
+}
+
+impl SeriesRequest {
+    pub fn to_series_db(&self) -> Result<SeriesDB, String> {
+        let slug = slugify(&self.slug.clone().filter(|slug| !slug.is_empty()).unwrap_or(self.name.clone()));
+        if slug.is_empty() {
+            return Err("Series name or slug must contain letters or numbers".to_string());
+        }
+
+        Ok(SeriesDB {
+            id: 1,
+            name: self.name.clone(),
+            slug,
+            description: Some(self.description.clone().unwrap_or("".to_string())),
+            published: self.published,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+            deleted_at: None,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SeriesPartsRequest {
+    pub post_ids: Vec<i32>
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeriesPart {
+    pub post_id: i32,
+    pub part: i32,
+    pub title: String,
+    pub slug: String
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeriesNav {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+    pub part: i32,
+    pub total: usize,
+    pub previous: Option<SeriesPart>,
+    pub next: Option<SeriesPart>
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeriesWithParts {
+    #[serde(flatten)]
+    pub series: SeriesDB,
+    pub parts: Vec<SeriesPart>
+}
+
+#[derive(Debug, Serialize)]
+pub struct PublishedSeriesPart {
+    pub part: i32,
+    #[serde(flatten)]
+    pub post: JoinedPost
+}
+
+#[derive(Debug, Serialize)]
+pub struct PublishedSeries {
+    #[serde(flatten)]
+    pub series: SeriesDB,
+    pub parts: Vec<PublishedSeriesPart>
+}
+
+// Pagination Request Struct
+#[derive(Debug, Deserialize)]
+pub struct PaginationParams {
+    pub page: Option<i32>,
+    pub limit: Option<i32>,
+    pub search: Option<String>
+}
+
+// Class Wide Function
+
+fn create_series(new_series: SeriesDB, conn: &mut DBPooledConnection) -> Result<SeriesDB, Error> {
+    use crate::schema::series::dsl::*;
+    diesel::insert_into(series)
+        .values((
+            name.eq(new_series.name),
+            slug.eq(new_series.slug),
+            description.eq(new_series.description),
+            published.eq(new_series.published),
+            created_at.eq(new_series.created_at),
+            updated_at.eq(new_series.updated_at),
+            deleted_at.eq(new_series.deleted_at),
+        ))
+        .get_result(conn)
+}
+
+fn update_series(updated_series: SeriesDB, series_id: i32, conn: &mut DBPooledConnection) -> Result<SeriesDB, Error> {
+    use crate::schema::series::dsl::*;
+    diesel::update(series.filter(id.eq(series_id)))
+        .set((
+            name.eq(updated_series.name),
+            slug.eq(updated_series.slug),
+            description.eq(updated_series.description),
+            published.eq(updated_series.published),
+            updated_at.eq(Utc::now().naive_utc()),
+        ))
+        .get_result(conn)
+}
+
+fn all_series_with_pagination(page: i32, limit: i32, search: String, conn: &mut DBPooledConnection) -> Result<Vec<SeriesDB>, Error> {
+    use crate::schema::series::dsl::*;
+    let mut query = series
+        .filter(deleted_at.is_null())
+        .order_by(id.desc())
+        .limit(limit as i64)
+        .offset(((page - 1) * limit) as i64)
+        .into_boxed();
+
+    if !search.is_empty() {
+        query = query.filter(
+            name.ilike(format!("%{}%", search))
+                .or(description.ilike(format!("%{}%", search)))
+        );
+    }
+
+    query.load::<SeriesDB>(conn)
+}
+
+fn series_parts(parts_series_id: i32, published_only: bool, conn: &mut DBPooledConnection) -> Result<Vec<SeriesPart>, Error> {
+    use crate::schema::series_posts::dsl::*;
+    use crate::schema::posts::dsl::{posts, title, slug, published, deleted_at};
+
+    let mut query = series_posts
+        .inner_join(posts)
+        .filter(series_id.eq(parts_series_id))
+        .filter(deleted_at.is_null())
+        .order_by(part_order.asc())
+        .select((post_id, part_order, title, slug))
+        .into_boxed();
+
+    if published_only {
+        query = query.filter(published.eq(true));
+    }
+
+    let parts = query
+        .load::<(i32, i32, String, String)>(conn)?
+        .into_iter()
+        .map(|(part_post_id, part, part_title, part_slug)| SeriesPart {
+            post_id: part_post_id,
+            part,
+            title: part_title,
+            slug: part_slug,
+        })
+        .collect();
+
+    Ok(parts)
+}
+
+pub fn series_nav(nav_post_id: i32, published_only: bool, conn: &mut DBPooledConnection) -> Result<Option<SeriesNav>, Error> {
+    use crate::schema::series_posts::dsl::{series_posts, post_id};
+    use crate::schema::series::dsl::{series, deleted_at, published};
+
+    let mut query = series_posts
+        .inner_join(series)
+        .filter(post_id.eq(nav_post_id))
+        .filter(deleted_at.is_null())
+        .select(SeriesDB::as_select())
+        .into_boxed();
+
+    if published_only {
+        query = query.filter(published.eq(true));
+    }
+
+    let current_series = match query.first::<SeriesDB>(conn).optional()? {
+        Some(current_series) => current_series,
+        None => return Ok(None),
+    };
+
+    let mut parts = series_parts(current_series.id, published_only, conn)?;
+    let total = parts.len();
+    let position = parts.iter().position(|part| part.post_id == nav_post_id);
+
+    let (previous, part, next) = match position {
+        Some(position) => {
+            let next = (position + 1 < parts.len()).then(|| parts.remove(position + 1));
+            let part = parts.remove(position).part;
+            let previous = position.checked_sub(1).map(|previous| parts.remove(previous));
+            (previous, part, next)
+        }
+        None => (None, 0, None),
+    };
+
+    Ok(Some(SeriesNav {
+        id: current_series.id,
+        name: current_series.name,
+        slug: current_series.slug,
+        part,
+        total,
+        previous,
+        next
+    }))
+}
+
+// Checked up front so a bad list is reported instead of failing on the foreign keys
+fn check_series_parts(parts_series_id: i32, ordered_post_ids: &[i32], conn: &mut DBPooledConnection) -> Result<(), SeriesError> {
+    use crate::schema::series::dsl::{series, id as series_table_id};
+    use crate::schema::posts::dsl::{posts, id as posts_id, deleted_at};
+
+    let series_exists = series
+        .filter(series_table_id.eq(parts_series_id))
+        .select(series_table_id)
+        .first::<i32>(conn)
+        .optional()?
+        .is_some();
+    if !series_exists {
+        return Err(SeriesError::NotFound(parts_series_id));
+    }
+
+    let mut seen_ids = HashSet::new();
+    if let Some(duplicate) = ordered_post_ids.iter().find(|part_post_id| !seen_ids.insert(**part_post_id)) {
+        return Err(SeriesError::DuplicatePost(*duplicate));
+    }
+
+    let known_ids: Vec<i32> = posts
+        .filter(posts_id.eq_any(ordered_post_ids))
+        .filter(deleted_at.is_null())
+        .select(posts_id)
+        .load(conn)?;
+    match ordered_post_ids.iter().find(|part_post_id| !known_ids.contains(part_post_id)) {
+        Some(unknown) => Err(SeriesError::UnknownPost(*unknown)),
+        None => Ok(()),
+    }
+}
+
+fn set_series_parts(parts_series_id: i32, ordered_post_ids: Vec<i32>, conn: &mut DBPooledConnection) -> Result<Vec<SeriesPart>, SeriesError> {
+    use crate::schema::series_posts::dsl::*;
+
+    conn.transaction(|conn| {
+        check_series_parts(parts_series_id, &ordered_post_ids, conn)?;
+
+        // A post belongs to a single series, listing it here moves it out of any other
+        diesel::delete(series_posts.filter(series_id.eq(parts_series_id).or(post_id.eq_any(&ordered_post_ids))))
+            .execute(conn)?;
+
+        for (position, part_post_id) in ordered_post_ids.iter().enumerate() {
+            diesel::insert_into(series_posts)
+                .values((
+                    series_id.eq(parts_series_id),
+                    post_id.eq(part_post_id),
+                    part_order.eq(position as i32 + 1),
+                ))
+                .execute(conn)?;
+        }
+
+        Ok(series_parts(parts_series_id, false, conn)?)
+    })
+}
+
+fn get_published_series(series_slug: String, conn: &mut DBPooledConnection) -> Result<PublishedSeries, Error> {
+    use crate::schema::series::dsl::*;
+
+    let published_series = series
+        .filter(slug.eq(series_slug))
+        .filter(published.eq(true))
+        .filter(deleted_at.is_null())
+        .first::<SeriesDB>(conn)?;
+
+    let parts = series_parts(published_series.id, true, conn)?;
+    let mut joined_posts = get_posts_by_ids(parts.iter().map(|part| part.post_id).collect(), conn)?;
+    to_listing(&mut joined_posts, conn);
+
+    let parts = parts
+        .into_iter()
+        .filter_map(|part| {
+            let index = joined_posts.iter().position(|joined| joined.post.id == part.post_id)?;
+            Some(PublishedSeriesPart {
+                part: part.part,
+                post: joined_posts.swap_remove(index),
+            })
+        })
+        .collect();
+
+    Ok(PublishedSeries {
+        series: published_series,
+        parts
+    })
+}
+
+fn save_error_response(action: &str, e: Error) -> HttpResponse {
+    match e {
+        Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => HttpResponse::Conflict()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error {} series: slug is already taken", action)),
+        _ => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error {} series: {}", action, e)),
+    }
+}
+
+// Routing
+
+#[post("/series")]
+pub async fn create(series_req: web::Json<SeriesRequest>, pool: web::Data<DBPool>) -> HttpResponse {
+    match series_req.to_series_db() {
+        Ok(series_db) => {
+            let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+            match create_series(series_db, &mut conn) {
+                Ok(inserted_series) => HttpResponse::Created()
+                    .content_type(APPLICATION_JSON)
+                    .json(inserted_series),
+                Err(e) => save_error_response("inserting", e),
+            }
+        }
+        Err(e) => HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(e),
+    }
+}
+
+#[post("/series/{id}")]
+pub async fn update(path: web::Path<i32>, series_req: web::Json<SeriesRequest>, pool: web::Data<DBPool>) -> HttpResponse {
+    let series_id = path.into_inner();
+    match series_req.to_series_db() {
+        Ok(series_db) => {
+            let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+            match update_series(series_db, series_id, &mut conn) {
+                Ok(updated_series) => HttpResponse::Created()
+                    .content_type(APPLICATION_JSON)
+                    .json(updated_series),
+                Err(e) => save_error_response("updating", e),
+            }
+        }
+        Err(e) => HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(e),
+    }
+}
+
+#[get("/series/{id}")]
+pub async fn get(path: web::Path<i32>, pool: web::Data<DBPool>) -> HttpResponse {
+    let series_id = path.into_inner();
+
+    use crate::schema::series::dsl::*;
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    let found_series = match series.filter(id.eq(series_id)).first::<SeriesDB>(&mut conn) {
+        Ok(found_series) => found_series,
+        Err(_) => return HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json("Series not found"),
+    };
+
+    match series_parts(series_id, false, &mut conn) {
+        Ok(parts) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(SeriesWithParts { series: found_series, parts }),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve series parts"})),
+    }
+}
+
+#[post("/series/{id}/parts")]
+pub async fn reorder(path: web::Path<i32>, parts_req: web::Json<SeriesPartsRequest>, pool: web::Data<DBPool>) -> HttpResponse {
+    let series_id = path.into_inner();
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match set_series_parts(series_id, parts_req.into_inner().post_ids, &mut conn) {
+        Ok(series_parts) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(series_parts),
+        Err(e @ SeriesError::NotFound(_)) => HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error ordering series parts: {}", e)),
+        Err(e @ (SeriesError::DuplicatePost(_) | SeriesError::UnknownPost(_))) => HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error ordering series parts: {}", e)),
+        Err(e) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error ordering series parts: {}", e)),
+    }
+}
+
+#[delete("/series/{id}")]
+pub async fn delete(path: web::Path<i32>, pool: web::Data<DBPool>) -> HttpResponse {
+    let series_id = path.into_inner();
+    let current_time = Utc::now().naive_utc();
+
+    use crate::schema::series::dsl::*;
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match diesel::update(series.filter(id.eq(series_id)))
+        .set(deleted_at.eq(Some(current_time)))
+        .execute(&mut conn)
+    {
+        Ok(_) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Series successfully deleted"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to delete series"})),
+    }
+}
+
+#[post("/series/{id}/restore")]
+pub async fn restore(path: web::Path<i32>, pool: web::Data<DBPool>) -> HttpResponse {
+    let series_id = path.into_inner();
+
+    use crate::schema::series::dsl::*;
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match diesel::update(series.filter(id.eq(series_id)))
+        .set(deleted_at.eq(None::<NaiveDateTime>))
+        .execute(&mut conn)
+    {
+        Ok(_) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Series successfully restored"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to restore series"})),
+    }
+}
+
+#[get("/series")]
+pub async fn all(query: web::Query<PaginationParams>, pool: web::Data<DBPool>) -> HttpResponse {
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(20);
+    let search = query.search.clone().unwrap_or("".to_string());
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match all_series_with_pagination(page, limit, search, &mut conn) {
+        Ok(series) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(series),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve series"})),
+    }
+}
+
+#[get("/series/{slug}")]
+pub async fn public(path: web::Path<String>, pool: web::Data<DBPool>) -> HttpResponse {
+    let series_slug = path.into_inner();
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match get_published_series(series_slug, &mut conn) {
+        Ok(published_series) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(published_series),
+        Err(Error::NotFound) => HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Series not found"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve series"})),
+    }
+}
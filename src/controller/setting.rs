@@ -8,6 +8,7 @@ use crate::constants::{APPLICATION_JSON, CONNECTION_POOL_ERROR};
 use crate::{DBPool, DBPooledConnection};
 
 use crate::models::SettingDB;
+use std::collections::HashMap;
 
 // Setting Request Struct
 #[derive(Debug, Deserialize, Serialize)]
@@ -31,6 +32,81 @@ impl SettingRequest {
     }
 }
 
+// Site wide settings used to build public facing documents
+#[derive(Debug)]
+pub struct SiteSettings {
+    pub title: String,
+    pub description: String,
+    pub url: String,
+    pub post_url_pattern: String,
+    pub category_url_pattern: String,
+    pub project_url_pattern: String,
+    pub static_pages: Vec<String>,
+    pub feed_size: i32
+}
+
+impl SiteSettings {
+    pub fn load(conn: &mut DBPooledConnection) -> SiteSettings {
+        let values = get_setting_values(&[
+            "site_title",
+            "site_description",
+            "site_url",
+            "post_url_pattern",
+            "category_url_pattern",
+            "project_url_pattern",
+            "sitemap_static_pages",
+            "feed_size",
+        ], conn);
+        let value_or = |key: &str, default: &str| values.get(key).cloned().unwrap_or(default.to_string());
+
+        SiteSettings {
+            title: value_or("site_title", "Sedikit Acak"),
+            description: value_or("site_description", ""),
+            url: value_or("site_url", "").trim_end_matches('/').to_string(),
+            post_url_pattern: value_or("post_url_pattern", "{site_url}/post/{slug}"),
+            category_url_pattern: value_or("category_url_pattern", "{site_url}/category/{slug}"),
+            project_url_pattern: value_or("project_url_pattern", "{site_url}/project/{id}"),
+            static_pages: value_or("sitemap_static_pages", "/")
+                .split(',')
+                .map(|page| page.trim().to_string())
+                .filter(|page| !page.is_empty())
+                .collect(),
+            feed_size: value_or("feed_size", "20").parse().unwrap_or(20),
+        }
+    }
+
+    fn fill_pattern(&self, pattern: &str, key: &str, value: &str) -> String {
+        pattern
+            .replace("{site_url}", &self.url)
+            .replace(key, value)
+    }
+
+    pub fn post_url(&self, slug: &str) -> String {
+        self.fill_pattern(&self.post_url_pattern, "{slug}", slug)
+    }
+
+    pub fn category_url(&self, slug: &str) -> String {
+        self.fill_pattern(&self.category_url_pattern, "{slug}", slug)
+    }
+
+    pub fn project_url(&self, id: i32) -> String {
+        self.fill_pattern(&self.project_url_pattern, "{id}", &id.to_string())
+    }
+
+    pub fn page_url(&self, path: &str) -> String {
+        format!("{}/{}", self.url, path.trim_start_matches('/'))
+    }
+
+    // Site relative paths such as `/assets/...` get the site URL, absolute URLs stay as they are
+    pub fn absolute_url(&self, url: &str) -> String {
+        if url.contains("://") {
+            url.to_string()
+        } else {
+            self.page_url(url)
+        }
+    }
+}
+
 // Pagination Request Struct
 #[derive(Debug, Deserialize)]
 pub struct PaginationParams {
@@ -86,6 +162,29 @@ fn all_setting_with_pagination(page: i32, limit: i32, search: String, conn: &mut
     query.load::<SettingDB>(conn)
 }
 
+pub fn get_setting_value(setting_param: &str, conn: &mut DBPooledConnection) -> Option<String> {
+    use crate::schema::settings::dsl::*;
+
+    settings
+        .filter(param.eq(setting_param))
+        .filter(deleted_at.is_null())
+        .select(value)
+        .first::<String>(conn)
+        .ok()
+}
+
+pub fn get_setting_values(setting_params: &[&str], conn: &mut DBPooledConnection) -> HashMap<String, String> {
+    use crate::schema::settings::dsl::*;
+
+    settings
+        .filter(param.eq_any(setting_params))
+        .filter(deleted_at.is_null())
+        .select((param, value))
+        .load::<(String, String)>(conn)
+        .map(|rows| rows.into_iter().collect())
+        .unwrap_or_default()
+}
+
 // Routing
 
 #[post("/setting")]
@@ -0,0 +1,153 @@
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use diesel::result::Error;
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods};
+
+use crate::constants::{APPLICATION_JSON, APPLICATION_XML, CONNECTION_POOL_ERROR};
+use crate::{DBPool, DBPooledConnection};
+
+use crate::controller::feed::conditional_response;
+use crate::controller::setting::SiteSettings;
+use crate::render::escape_xml;
+
+// Search engines refuse sitemaps with more URLs than this
+const SITEMAP_URL_LIMIT: usize = 50_000;
+
+// Sitemap Struct
+pub struct SitemapUrl {
+    pub loc: String,
+    pub lastmod: Option<NaiveDateTime>
+}
+
+// Class Wide Function
+
+fn collect_urls(conn: &mut DBPooledConnection) -> Result<Vec<SitemapUrl>, Error> {
+    use crate::schema::posts::dsl::{posts, slug as post_slug, updated_at as post_updated_at, deleted_at as post_deleted_at, published as post_published};
+    use crate::schema::post_categories::dsl::{post_categories, slug as category_slug, updated_at as category_updated_at, deleted_at as category_deleted_at, published as category_published};
+    use crate::schema::projects::dsl::{projects, id as project_id, updated_at as project_updated_at, deleted_at as project_deleted_at, published as project_published};
+
+    let site = SiteSettings::load(conn);
+
+    let mut urls: Vec<SitemapUrl> = site.static_pages
+        .iter()
+        .map(|static_page| SitemapUrl { loc: site.page_url(static_page), lastmod: None })
+        .collect();
+
+    let post_rows = posts
+        .inner_join(post_categories)
+        .filter(post_deleted_at.is_null())
+        .filter(post_published.eq(true))
+        .filter(category_deleted_at.is_null())
+        .order_by(post_updated_at.desc())
+        .select((post_slug, post_updated_at))
+        .load::<(String, NaiveDateTime)>(conn)?;
+    urls.extend(post_rows.into_iter().map(|(slug, updated)| SitemapUrl { loc: site.post_url(&slug), lastmod: Some(updated) }));
+
+    let category_rows = post_categories
+        .filter(category_deleted_at.is_null())
+        .filter(category_published.eq(true))
+        .select((category_slug, category_updated_at))
+        .load::<(String, NaiveDateTime)>(conn)?;
+    urls.extend(category_rows.into_iter().map(|(slug, updated)| SitemapUrl { loc: site.category_url(&slug), lastmod: Some(updated) }));
+
+    let project_rows = projects
+        .filter(project_deleted_at.is_null())
+        .filter(project_published.eq(true))
+        .select((project_id, project_updated_at))
+        .load::<(i32, NaiveDateTime)>(conn)?;
+    urls.extend(project_rows.into_iter().map(|(id, updated)| SitemapUrl { loc: site.project_url(id), lastmod: Some(updated) }));
+
+    Ok(urls)
+}
+
+fn w3c_date(datetime: &NaiveDateTime) -> String {
+    Utc.from_utc_datetime(datetime).to_rfc3339()
+}
+
+fn last_modified(urls: &[SitemapUrl]) -> Option<NaiveDateTime> {
+    urls.iter().filter_map(|url| url.lastmod).max()
+}
+
+fn urlset_document(urls: &[SitemapUrl]) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+
+    for url in urls {
+        xml.push_str("<url>");
+        xml.push_str(&format!("<loc>{}</loc>", escape_xml(&url.loc)));
+        if let Some(lastmod) = &url.lastmod {
+            xml.push_str(&format!("<lastmod>{}</lastmod>", w3c_date(lastmod)));
+        }
+        xml.push_str("</url>\n");
+    }
+
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+fn index_document(http_req: &HttpRequest, urls: &[SitemapUrl]) -> String {
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+
+    for (chunk_index, chunk) in urls.chunks(SITEMAP_URL_LIMIT).enumerate() {
+        let loc = http_req
+            .url_for("sitemap_page", [(chunk_index + 1).to_string()])
+            .map(|url| url.to_string())
+            .unwrap_or_default();
+
+        xml.push_str("<sitemap>");
+        xml.push_str(&format!("<loc>{}</loc>", escape_xml(&loc)));
+        if let Some(lastmod) = last_modified(chunk) {
+            xml.push_str(&format!("<lastmod>{}</lastmod>", w3c_date(&lastmod)));
+        }
+        xml.push_str("</sitemap>\n");
+    }
+
+    xml.push_str("</sitemapindex>\n");
+    xml
+}
+
+fn sitemap_error() -> HttpResponse {
+    HttpResponse::InternalServerError()
+        .content_type(APPLICATION_JSON)
+        .json(serde_json::json!({"message": "Failed to build sitemap"}))
+}
+
+// Routing
+
+#[get("/sitemap.xml")]
+pub async fn index(http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    let urls = match collect_urls(&mut conn) {
+        Ok(urls) => urls,
+        Err(_) => return sitemap_error(),
+    };
+
+    let body = if urls.len() > SITEMAP_URL_LIMIT {
+        index_document(&http_req, &urls)
+    } else {
+        urlset_document(&urls)
+    };
+
+    conditional_response(&http_req, body, APPLICATION_XML, last_modified(&urls))
+}
+
+#[get("/sitemap-{page:\\d+}.xml", name = "sitemap_page")]
+pub async fn page(path: web::Path<usize>, http_req: HttpRequest, pool: web::Data<DBPool>) -> HttpResponse {
+    let page_number = path.into_inner();
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    let urls = match collect_urls(&mut conn) {
+        Ok(urls) => urls,
+        Err(_) => return sitemap_error(),
+    };
+
+    match urls.chunks(SITEMAP_URL_LIMIT).nth(page_number.saturating_sub(1)) {
+        Some(chunk) if page_number > 0 => conditional_response(&http_req, urlset_document(chunk), APPLICATION_XML, last_modified(chunk)),
+        _ => HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Sitemap page not found"})),
+    }
+}
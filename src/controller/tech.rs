@@ -2,9 +2,13 @@ use actix_web::{post, get, delete, web, HttpResponse};
 use chrono::{Utc, NaiveDateTime};
 use serde::{Serialize, Deserialize};
 use diesel::result::Error;
-use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, PgTextExpressionMethods};
+use diesel::dsl::count;
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, PgTextExpressionMethods, TextExpressionMethods, BoolExpressionMethods, JoinOnDsl, NullableExpressionMethods, SelectableHelper};
+use std::collections::HashMap;
 
 use crate::constants::{APPLICATION_JSON, CONNECTION_POOL_ERROR};
+use crate::slug::{slugify, unique_slug};
+use crate::sort::UsageSort;
 use crate::{DBPool, DBPooledConnection};
 
 use crate::models::TechDB;
@@ -13,6 +17,7 @@ use crate::models::TechDB;
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TechRequest {
     pub title: String,
+    pub slug: Option<String>,
     pub icon: Option<String>
 }
 
@@ -24,7 +29,8 @@ impl TechRequest {
             icon: self.icon.clone(),
             created_at: Utc::now().naive_utc(),
             updated_at: Utc::now().naive_utc(),
-            deleted_at: None
+            deleted_at: None,
+            slug: slugify(&self.slug.clone().filter(|slug| !slug.is_empty()).unwrap_or(self.title.clone()))
         })
     }
 }
@@ -37,13 +43,98 @@ pub struct PaginationParams {
     pub search: Option<String>
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UsageParams {
+    pub sort: Option<String>
+}
+
+// A technology with how many published projects use it
+#[derive(Debug, Serialize)]
+pub struct TechUsage {
+    #[serde(flatten)]
+    pub tech: TechDB,
+    pub project_count: i64
+}
+
 // Class Wide Function
 
+// The requested slug, or a numbered variant when another tech already uses it
+fn free_slug(base: &str, own_tech_id: Option<i32>, conn: &mut DBPooledConnection) -> Result<String, Error> {
+    use crate::schema::techs::dsl::*;
+
+    let base = if base.is_empty() { "tech" } else { base };
+    let taken = techs
+        .filter(slug.eq(base).or(slug.like(format!("{}-%", base))))
+        .filter(id.ne(own_tech_id.unwrap_or(0)))
+        .select(slug)
+        .load::<String>(conn)?;
+
+    Ok(unique_slug(base, &taken))
+}
+
+// The tech id each identifier (an id or a slug) names, unknown ones are left out
+pub fn resolve_tech_ids(identifiers: &[String], conn: &mut DBPooledConnection) -> Result<HashMap<String, i32>, Error> {
+    use crate::schema::techs::dsl::*;
+
+    let requested_ids: Vec<i32> = identifiers.iter().filter_map(|identifier| identifier.parse().ok()).collect();
+    let found = techs
+        .filter(deleted_at.is_null())
+        .filter(id.eq_any(requested_ids).or(slug.eq_any(identifiers)))
+        .select((id, slug))
+        .load::<(i32, String)>(conn)?;
+
+    Ok(identifiers
+        .iter()
+        .filter_map(|identifier| {
+            found
+                .iter()
+                .find(|(tech_id, tech_slug)| identifier.parse() == Ok(*tech_id) || tech_slug == identifier)
+                .map(|(tech_id, _)| (identifier.clone(), *tech_id))
+        })
+        .collect())
+}
+
+fn tech_usage(sort: UsageSort, conn: &mut DBPooledConnection) -> Result<Vec<TechUsage>, Error> {
+    use crate::schema::techs::dsl::*;
+    use crate::schema::projects_techs::dsl::{projects_techs, project_id, tech_id};
+    use crate::schema::projects::dsl::{projects, id as projects_id, published, deleted_at as project_deleted_at};
+
+    // Only links to published projects are joined, so the others count as unused
+    let published_projects = projects
+        .filter(published.eq(true))
+        .filter(project_deleted_at.is_null())
+        .select(projects_id);
+
+    let mut query = techs
+        .left_join(projects_techs.on(
+            tech_id.eq(id)
+                .and(project_id.eq_any(published_projects))
+        ))
+        .filter(deleted_at.is_null())
+        .group_by(id)
+        .select((TechDB::as_select(), count(project_id.nullable())))
+        .into_boxed();
+
+    // Most used first, the title keeps ties in a stable order
+    query = match sort {
+        UsageSort::Name => query.order_by(title.asc()),
+        UsageSort::Count => query.order_by((count(project_id.nullable()).desc(), title.asc())),
+    };
+
+    Ok(query
+        .load::<(TechDB, i64)>(conn)?
+        .into_iter()
+        .map(|(tech, project_count)| TechUsage { tech, project_count })
+        .collect())
+}
+
 fn create_tech(tech: TechDB, conn: &mut DBPooledConnection) -> Result<TechDB, Error> {
     use crate::schema::techs::dsl::*;
+    let new_slug = free_slug(&tech.slug, None, conn)?;
     diesel::insert_into(techs)
         .values((
             title.eq(tech.title),
+            slug.eq(new_slug),
             icon.eq(tech.icon),
             created_at.eq(tech.created_at),
             updated_at.eq(tech.updated_at),
@@ -54,9 +145,11 @@ fn create_tech(tech: TechDB, conn: &mut DBPooledConnection) -> Result<TechDB, Er
 
 fn update_tech(tech: TechDB, tech_id: i32, conn: &mut DBPooledConnection) -> Result<TechDB, Error> {
     use crate::schema::techs::dsl::*;
+    let new_slug = free_slug(&tech.slug, Some(tech_id), conn)?;
     diesel::update(techs.filter(id.eq(tech_id)))
         .set((
             title.eq(tech.title),
+            slug.eq(new_slug),
             icon.eq(tech.icon),
             updated_at.eq(Utc::now().naive_utc())
         ))
@@ -198,4 +291,24 @@ pub async fn all(query: web::Query<PaginationParams>, pool: web::Data<DBPool>) -
             .content_type(APPLICATION_JSON)
             .json(serde_json::json!({"message": "Failed to retrieve technologies"})),
     }
-}
\ No newline at end of file
+}
+
+#[get("/techs")]
+pub async fn public(query: web::Query<UsageParams>, pool: web::Data<DBPool>) -> HttpResponse {
+    let sort = match UsageSort::parse(query.sort.as_deref()) {
+        Ok(sort) => sort,
+        Err(message) => return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": message})),
+    };
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match tech_usage(sort, &mut conn) {
+        Ok(techs) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(techs),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve technologies"})),
+    }
+}
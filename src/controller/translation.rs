@@ -0,0 +1,266 @@
+use actix_web::{post, get, delete, web, HttpRequest, HttpResponse};
+use actix_web::http::header;
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use diesel::result::{Error, DatabaseErrorKind};
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, Connection};
+
+use crate::constants::{APPLICATION_JSON, CONNECTION_POOL_ERROR};
+use crate::errors::SlugError;
+use crate::slug::slugify;
+use crate::{DBPool, DBPooledConnection};
+
+use crate::controller::post::{JoinedPost, SeoMeta};
+use crate::controller::setting::get_setting_value;
+use crate::models::PostTranslationDB;
+
+// Translation Request Struct
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TranslationRequest {
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub slug: Option<String>,
+    pub content: String
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LocaleParams {
+    pub lang: Option<String>
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostAlternate {
+    pub locale: String,
+    pub slug: String
+}
+
+// Class Wide Function
+
+// Lowercases a BCP 47 style tag such as "en" or "en-US", None when it isn't one
+pub fn normalize_locale(tag: &str) -> Option<String> {
+    let tag = tag.trim().replace('_', "-").to_lowercase();
+    let mut parts = tag.split('-');
+
+    let language = parts.next()?;
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    match parts.next() {
+        None => Some(tag),
+        Some(region) if (2..=4).contains(&region.len()) && region.chars().all(|c| c.is_ascii_alphanumeric()) && parts.next().is_none() => Some(tag),
+        Some(_) => None,
+    }
+}
+
+pub fn default_locale(conn: &mut DBPooledConnection) -> String {
+    get_setting_value("default_locale", conn)
+        .and_then(|locale| normalize_locale(&locale))
+        .unwrap_or("id".to_string())
+}
+
+// Locales the reader asked for, best first: `lang` wins over Accept-Language
+pub fn requested_locales(lang: Option<&str>, http_req: &HttpRequest) -> Vec<String> {
+    let mut weighted: Vec<(f32, String)> = http_req.headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|accept| accept.to_str().ok())
+        .unwrap_or("")
+        .split(',')
+        .filter_map(|entry| {
+            let mut params = entry.split(';');
+            let locale = normalize_locale(params.next()?)?;
+            let quality = params
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|quality| quality.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (quality > 0.0).then_some((quality, locale))
+        })
+        .collect();
+    weighted.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut locales: Vec<String> = Vec::new();
+    let explicit = lang.and_then(normalize_locale);
+    for locale in explicit.into_iter().chain(weighted.into_iter().map(|(_, locale)| locale)) {
+        // "en-us" also accepts a plain "en" translation
+        let primary = locale.split('-').next().unwrap_or("").to_string();
+        for candidate in [locale, primary] {
+            if !locales.contains(&candidate) {
+                locales.push(candidate);
+            }
+        }
+    }
+
+    locales
+}
+
+pub fn translation_by_slug(translation_slug: &str, conn: &mut DBPooledConnection) -> Result<(i32, String), Error> {
+    use crate::schema::post_translations::dsl::*;
+
+    post_translations
+        .filter(slug.eq(translation_slug))
+        .select((post_id, locale))
+        .first::<(i32, String)>(conn)
+}
+
+// Swaps in the best matching translation of every post and lists the alternates for hreflang
+pub fn localize_posts(joined_posts: &mut [JoinedPost], requested: &[String], conn: &mut DBPooledConnection) -> Result<(), Error> {
+    use crate::schema::post_translations::dsl::*;
+
+    let fallback = default_locale(conn);
+    let post_ids: Vec<i32> = joined_posts.iter().map(|joined| joined.post.id).collect();
+    let mut translations = post_translations
+        .filter(post_id.eq_any(post_ids))
+        .order_by(locale.asc())
+        .load::<PostTranslationDB>(conn)?;
+
+    for joined in joined_posts.iter_mut() {
+        let mut alternates = vec![PostAlternate { locale: fallback.clone(), slug: joined.post.slug.clone() }];
+        alternates.extend(translations
+            .iter()
+            .filter(|translation| translation.post_id == joined.post.id)
+            .map(|translation| PostAlternate { locale: translation.locale.clone(), slug: translation.slug.clone() }));
+
+        let chosen = requested
+            .iter()
+            .find(|wanted| alternates.iter().any(|alternate| &alternate.locale == *wanted))
+            .cloned()
+            .unwrap_or(fallback.clone());
+
+        if let Some(index) = translations.iter().position(|translation| translation.post_id == joined.post.id && translation.locale == chosen) {
+            let translation = translations.swap_remove(index);
+            joined.post.title = translation.title;
+            joined.post.subtitle = translation.subtitle;
+            joined.post.slug = translation.slug;
+            joined.post.content = translation.content;
+            joined.post.content_blocks = None;
+            // Meta texts and the manual excerpt are written in the default locale, the translation's own title and subtitle fit better
+            joined.post.meta_title = None;
+            joined.post.meta_description = None;
+            joined.post.excerpt = None;
+            joined.seo = SeoMeta::from_post(&joined.post);
+        }
+
+        joined.locale = Some(chosen);
+        joined.alternates = alternates;
+    }
+
+    Ok(())
+}
+
+fn save_translation(translation_post_id: i32, translation_locale: String, translation: TranslationRequest, conn: &mut DBPooledConnection) -> Result<PostTranslationDB, SlugError> {
+    use crate::schema::post_translations::dsl::*;
+    use crate::schema::post_slugs::dsl::{post_slugs, slug as used_slug};
+
+    let new_slug = slugify(&translation.slug.clone().filter(|requested| !requested.is_empty()).unwrap_or(translation.title.clone()));
+
+    conn.transaction(|conn| {
+        // Translated slugs share the /post/{slug} namespace with every slug a post has used,
+        // clashes with other translations are left to the unique index
+        let used_by_post = post_slugs
+            .filter(used_slug.eq(&new_slug))
+            .count()
+            .get_result::<i64>(conn)? > 0;
+        if new_slug.is_empty() || used_by_post {
+            return Err(SlugError::Taken(new_slug.clone()));
+        }
+
+        let current_time = Utc::now().naive_utc();
+        let saved = diesel::insert_into(post_translations)
+            .values((
+                post_id.eq(translation_post_id),
+                locale.eq(&translation_locale),
+                title.eq(&translation.title),
+                subtitle.eq(&translation.subtitle),
+                slug.eq(&new_slug),
+                content.eq(&translation.content),
+                created_at.eq(current_time),
+                updated_at.eq(current_time),
+            ))
+            .on_conflict((post_id, locale))
+            .do_update()
+            .set((
+                title.eq(&translation.title),
+                subtitle.eq(&translation.subtitle),
+                slug.eq(&new_slug),
+                content.eq(&translation.content),
+                updated_at.eq(current_time),
+            ))
+            .get_result(conn)?;
+
+        Ok(saved)
+    })
+}
+
+// Routing
+
+#[get("/post/{id}/translations")]
+pub async fn all(path: web::Path<i32>, pool: web::Data<DBPool>) -> HttpResponse {
+    let translation_post_id = path.into_inner();
+
+    use crate::schema::post_translations::dsl::*;
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match post_translations
+        .filter(post_id.eq(translation_post_id))
+        .order_by(locale.asc())
+        .load::<PostTranslationDB>(&mut conn)
+    {
+        Ok(translations) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(translations),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve translations"})),
+    }
+}
+
+#[post("/post/{id}/translation/{locale}")]
+pub async fn save(path: web::Path<(i32, String)>, translation_req: web::Json<TranslationRequest>, pool: web::Data<DBPool>) -> HttpResponse {
+    let (translation_post_id, requested_locale) = path.into_inner();
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    let translation_locale = match normalize_locale(&requested_locale) {
+        Some(translation_locale) if translation_locale != default_locale(&mut conn) => translation_locale,
+        Some(_) => return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json("The default locale is the post itself, update the post instead"),
+        None => return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Invalid locale: {}", requested_locale)),
+    };
+
+    match save_translation(translation_post_id, translation_locale, translation_req.into_inner(), &mut conn) {
+        Ok(saved) => HttpResponse::Created()
+            .content_type(APPLICATION_JSON)
+            .json(saved),
+        Err(e @ (SlugError::Taken(_) | SlugError::DatabaseError(Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)))) => HttpResponse::Conflict()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error saving translation: {}", e)),
+        Err(e) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(format!("Error saving translation: {}", e)),
+    }
+}
+
+#[delete("/post/{id}/translation/{locale}")]
+pub async fn delete(path: web::Path<(i32, String)>, pool: web::Data<DBPool>) -> HttpResponse {
+    let (translation_post_id, requested_locale) = path.into_inner();
+    let translation_locale = normalize_locale(&requested_locale).unwrap_or(requested_locale);
+
+    use crate::schema::post_translations::dsl::*;
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match diesel::delete(post_translations.filter(post_id.eq(translation_post_id)).filter(locale.eq(translation_locale)))
+        .execute(&mut conn)
+    {
+        Ok(0) => HttpResponse::NotFound()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Translation not found"})),
+        Ok(_) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Translation successfully deleted"})),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to delete translation"})),
+    }
+}
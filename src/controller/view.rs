@@ -0,0 +1,232 @@
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use actix_web::http::header;
+use chrono::{Utc, NaiveDate, Duration};
+use serde::{Serialize, Deserialize};
+use diesel::result::Error;
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, Connection};
+use sha2::{Sha256, Digest};
+use std::env;
+
+use crate::constants::{APPLICATION_JSON, CONNECTION_POOL_ERROR, MAX_PAGE_LIMIT};
+use crate::{DBPool, DBPooledConnection};
+
+use crate::controller::post::{JoinedPost, get_posts_by_ids, to_listing};
+use crate::models::PostDailyViewDB;
+
+// View Request Struct
+#[derive(Debug, Deserialize)]
+pub struct PopularParams {
+    pub period: Option<String>,
+    pub limit: Option<i32>
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsParams {
+    pub days: Option<i64>
+}
+
+#[derive(Debug, Serialize)]
+pub struct PopularPost {
+    #[serde(flatten)]
+    pub post: JoinedPost,
+    pub views: i64
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostViewStats {
+    pub post_id: i32,
+    pub total: i64,
+    pub daily: Vec<PostDailyViewDB>
+}
+
+// Class Wide Function
+
+// The connecting address, forwarded headers are only believed when they come from TRUSTED_PROXY
+fn client_ip(http_req: &HttpRequest) -> String {
+    let peer_ip = http_req.peer_addr().map(|peer| peer.ip().to_string()).unwrap_or_default();
+    match env::var("TRUSTED_PROXY") {
+        Ok(proxy) if !peer_ip.is_empty() && proxy == peer_ip => http_req
+            .connection_info()
+            .realip_remote_addr()
+            .unwrap_or(&peer_ip)
+            .to_string(),
+        _ => peer_ip,
+    }
+}
+
+// Hashes the visitor together with a salt and a scope so the raw IP never
+// reaches the database and hashes can't be correlated across scopes.
+pub fn visitor_hash(http_req: &HttpRequest, scope: &str) -> String {
+    let ip_address = client_ip(http_req);
+    let user_agent = http_req.headers()
+        .get(header::USER_AGENT)
+        .and_then(|agent| agent.to_str().ok())
+        .unwrap_or("");
+    // Checked at startup, without it the hash could be brute forced back to the IP
+    let salt = env::var("VISITOR_HASH_SALT").expect("VISITOR_HASH_SALT");
+
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(scope.as_bytes());
+    hasher.update(ip_address.as_bytes());
+    hasher.update(user_agent.as_bytes());
+
+    hex::encode(hasher.finalize())
+}
+
+pub fn record_view(view_post_id: i32, http_req: &HttpRequest, conn: &mut DBPooledConnection) -> Result<bool, Error> {
+    use crate::schema::post_views::dsl::{post_views, post_id as pv_post_id, visitor_hash as pv_visitor_hash, viewed_on};
+    use crate::schema::post_daily_views::dsl::*;
+
+    let today = Utc::now().date_naive();
+    let hash = visitor_hash(http_req, &format!("view:{}", today));
+
+    conn.transaction(|conn| {
+        let inserted = diesel::insert_into(post_views)
+            .values((
+                pv_post_id.eq(view_post_id),
+                pv_visitor_hash.eq(hash),
+                viewed_on.eq(today),
+            ))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        diesel::insert_into(post_daily_views)
+            .values((
+                post_id.eq(view_post_id),
+                view_date.eq(today),
+                views.eq(1),
+            ))
+            .on_conflict((post_id, view_date))
+            .do_update()
+            .set(views.eq(views + 1))
+            .execute(conn)?;
+
+        // Hashes are only needed to dedupe the current day, older ones are already rolled up
+        diesel::delete(post_views.filter(viewed_on.lt(today)))
+            .execute(conn)?;
+
+        Ok(true)
+    })
+}
+
+// First day of a window of `days` ending today, None when it doesn't fit the calendar
+fn window_start(days: i64) -> Option<NaiveDate> {
+    if days <= 0 {
+        return None;
+    }
+
+    Utc::now().date_naive().checked_sub_signed(Duration::try_days(days - 1)?)
+}
+
+fn parse_period(period: &str) -> Option<NaiveDate> {
+    if period == "all" {
+        return Some(NaiveDate::MIN);
+    }
+
+    window_start(period.strip_suffix('d')?.parse::<i64>().ok()?)
+}
+
+fn popular_posts(since: NaiveDate, limit: i32, conn: &mut DBPooledConnection) -> Result<Vec<PopularPost>, Error> {
+    use crate::schema::post_daily_views::dsl::*;
+    use crate::schema::posts::dsl::{posts, deleted_at as post_deleted_at, published as post_published};
+
+    let ranking: Vec<(i32, Option<i64>)> = post_daily_views
+        .inner_join(posts)
+        .filter(view_date.ge(since))
+        .filter(post_deleted_at.is_null())
+        .filter(post_published.eq(true))
+        .group_by(post_id)
+        .select((post_id, diesel::dsl::sum(views)))
+        .order_by(diesel::dsl::sum(views).desc())
+        .limit(limit as i64)
+        .load(conn)?;
+
+    let ids: Vec<i32> = ranking.iter().map(|(ranked_id, _)| *ranked_id).collect();
+    let mut joined_posts = get_posts_by_ids(ids, conn)?;
+    to_listing(&mut joined_posts, conn);
+
+    let result = ranking
+        .into_iter()
+        .filter_map(|(ranked_id, total)| {
+            let index = joined_posts.iter().position(|joined| joined.post.id == ranked_id)?;
+            Some(PopularPost {
+                post: joined_posts.swap_remove(index),
+                views: total.unwrap_or(0),
+            })
+        })
+        .collect();
+
+    Ok(result)
+}
+
+fn post_view_stats(stat_post_id: i32, since: NaiveDate, conn: &mut DBPooledConnection) -> Result<PostViewStats, Error> {
+    use crate::schema::post_daily_views::dsl::*;
+
+    let total: Option<i64> = post_daily_views
+        .filter(post_id.eq(stat_post_id))
+        .select(diesel::dsl::sum(views))
+        .first(conn)?;
+
+    let daily = post_daily_views
+        .filter(post_id.eq(stat_post_id))
+        .filter(view_date.ge(since))
+        .order_by(view_date.asc())
+        .load::<PostDailyViewDB>(conn)?;
+
+    Ok(PostViewStats {
+        post_id: stat_post_id,
+        total: total.unwrap_or(0),
+        daily
+    })
+}
+
+// Routing
+
+#[get("/posts/popular")]
+pub async fn popular(query: web::Query<PopularParams>, pool: web::Data<DBPool>) -> HttpResponse {
+    let period = query.period.clone().unwrap_or("30d".to_string());
+    let limit = query.limit.unwrap_or(10).clamp(1, MAX_PAGE_LIMIT);
+
+    let since = match parse_period(&period) {
+        Some(since) => since,
+        None => return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Invalid period, use e.g. 7d, 30d or all"})),
+    };
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match popular_posts(since, limit, &mut conn) {
+        Ok(posts) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(posts),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve popular posts"})),
+    }
+}
+
+#[get("/post/{id}/views")]
+pub async fn stats(path: web::Path<i32>, query: web::Query<StatsParams>, pool: web::Data<DBPool>) -> HttpResponse {
+    let post_id = path.into_inner();
+    let since = match window_start(query.days.unwrap_or(30)) {
+        Some(since) => since,
+        None => return HttpResponse::BadRequest()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Invalid days, use a positive number of days"})),
+    };
+
+    let mut conn = pool.get().expect(CONNECTION_POOL_ERROR);
+    match post_view_stats(post_id, since, &mut conn) {
+        Ok(stats) => HttpResponse::Ok()
+            .content_type(APPLICATION_JSON)
+            .json(stats),
+        Err(_) => HttpResponse::InternalServerError()
+            .content_type(APPLICATION_JSON)
+            .json(serde_json::json!({"message": "Failed to retrieve post views"})),
+    }
+}
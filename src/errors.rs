@@ -1,6 +1,12 @@
 use thiserror::Error;
 use diesel::result::Error as DieselError;
 use lettre::transport::smtp::Error as LettreError;
+use lettre::error::Error as MessageError;
+use lettre::address::AddressError;
+use std::env::VarError;
+use std::io::Error as IoError;
+use zip::result::ZipError;
+use quick_xml::Error as XmlError;
 
 #[derive(Debug, Error)]
 pub enum ContactError {
@@ -9,4 +15,125 @@ pub enum ContactError {
 
     #[error("Email sending error: {0}")]
     EmailError(#[from] LettreError),
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Error)]
+pub enum NotifyError {
+    #[error("Database error: {0}")]
+    Database(#[from] DieselError),
+
+    #[error("Mail configuration error: {0}")]
+    Config(#[from] VarError),
+
+    #[error("Email address error: {0}")]
+    Address(#[from] AddressError),
+
+    #[error("Email building error: {0}")]
+    Message(#[from] MessageError),
+
+    #[error("Email sending error: {0}")]
+    Email(#[from] LettreError),
+}
+
+#[derive(Debug, Error)]
+pub enum SlugError {
+    #[error("Slug {0} is already used by another post")]
+    Taken(String),
+
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+}
+
+#[derive(Debug, Error)]
+pub enum CategoryError {
+    #[error("Category {0} can't be moved below itself or one of its descendants")]
+    Cycle(i32),
+
+    #[error("Category still holds {0} posts, give a category to move them to")]
+    HasPosts(i64),
+
+    #[error("Category {0} can't take over the posts")]
+    InvalidTarget(i32),
+
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+}
+
+#[derive(Debug, Error)]
+pub enum SeriesError {
+    #[error("Series {0} doesn't exist")]
+    NotFound(i32),
+
+    #[error("Post {0} is listed more than once")]
+    DuplicatePost(i32),
+
+    #[error("Post {0} doesn't exist")]
+    UnknownPost(i32),
+
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+}
+
+#[derive(Debug, Error)]
+pub enum ReorderError {
+    #[error("ids must list every row exactly once")]
+    Mismatch,
+
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+}
+
+#[derive(Debug, Error)]
+pub enum TechFilterError {
+    #[error("Unknown tech {0}")]
+    UnknownTech(String),
+
+    #[error("Invalid match {0}, use any or all")]
+    InvalidMatch(String),
+
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+}
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Database error: {0}")]
+    Database(#[from] DieselError),
+
+    #[error("Front matter error: {0}")]
+    FrontMatter(#[from] serde_yaml::Error),
+
+    #[error("File error: {0}")]
+    Io(#[from] IoError),
+
+    #[error("Zip error: {0}")]
+    Zip(#[from] ZipError),
+}
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("Front matter error: {0}")]
+    FrontMatter(String),
+
+    #[error("{0}")]
+    Slug(#[from] SlugError),
+
+    #[error("Database error: {0}")]
+    Database(#[from] DieselError),
+
+    #[error("File error: {0}")]
+    Io(#[from] IoError),
+
+    #[error("Zip error: {0}")]
+    Zip(#[from] ZipError),
+
+    #[error("XML error: {0}")]
+    Xml(#[from] XmlError),
+
+    #[error("{0} is larger than {1} bytes")]
+    TooLarge(String, u64),
+
+    // Raised on purpose to roll back the transaction of a dry run
+    #[error("Dry run")]
+    DryRun,
+}
@@ -0,0 +1,391 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use diesel::result::Error;
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, OptionalExtension, Connection};
+use sanitize_filename::sanitize;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use zip::ZipArchive;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+
+use crate::DBPooledConnection;
+use crate::constants::MAX_IMPORT_ENTRY_SIZE;
+use crate::controller::image::unique_upload_path;
+use crate::controller::post::create_post;
+use crate::errors::ImportError;
+use crate::models::PostDB;
+use crate::slug::slugify;
+
+pub const DEFAULT_CATEGORY: &str = "Uncategorized";
+
+// Import Option Struct
+pub struct ImportOptions {
+    pub author_id: Uuid,
+    pub dry_run: bool
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportFileReport {
+    pub file: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub warnings: Vec<String>
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub imported: usize,
+    pub failed: usize,
+    pub files: Vec<ImportFileReport>
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum FrontMatterTags {
+    List(Vec<String>),
+    Text(String)
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FrontMatter {
+    title: Option<String>,
+    subtitle: Option<String>,
+    slug: Option<String>,
+    date: Option<String>,
+    category: Option<String>,
+    tags: Option<FrontMatterTags>,
+    published: Option<bool>
+}
+
+// Class Wide Function
+
+// Splits the YAML block between the leading `---` lines from the Markdown body
+fn split_front_matter(text: &str) -> Result<(FrontMatter, String), ImportError> {
+    let text = text.trim_start_matches('\u{feff}');
+    let mut lines = text.lines();
+    if lines.next().map(str::trim_end) != Some("---") {
+        return Err(ImportError::FrontMatter("file doesn't start with a --- front matter block".to_string()));
+    }
+
+    let mut yaml = String::new();
+    let mut closed = false;
+    for line in lines.by_ref() {
+        if matches!(line.trim_end(), "---" | "...") {
+            closed = true;
+            break;
+        }
+        yaml.push_str(line);
+        yaml.push('\n');
+    }
+    if !closed {
+        return Err(ImportError::FrontMatter("front matter block is never closed".to_string()));
+    }
+
+    let front_matter = if yaml.trim().is_empty() {
+        FrontMatter::default()
+    } else {
+        serde_yaml::from_str(&yaml).map_err(|e| ImportError::FrontMatter(e.to_string()))?
+    };
+    let body = lines.collect::<Vec<&str>>().join("\n");
+
+    Ok((front_matter, body.trim_start_matches('\n').to_string()))
+}
+
+pub fn parse_date(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value).map(|date| date.naive_utc()).ok()
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok())
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").ok())
+        .or_else(|| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").ok())
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().and_then(|date| date.and_hms_opt(0, 0, 0)))
+}
+
+fn is_relative_reference(url: &str) -> bool {
+    !(url.is_empty()
+        || url.starts_with('/')
+        || url.starts_with('#')
+        || url.starts_with("data:")
+        || url.contains("://"))
+}
+
+// Resolves `../img/a.png` against the directory of the Markdown file inside the zip
+fn resolve_zip_path(markdown_path: &str, reference: &str) -> Option<String> {
+    let reference = reference.split(['?', '#']).next().unwrap_or("").replace("%20", " ");
+    let base = Path::new(markdown_path).parent().unwrap_or(Path::new(""));
+
+    let mut parts: Vec<String> = Vec::new();
+    for component in base.join(reference).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::ParentDir => {
+                parts.pop()?;
+            }
+            _ => {}
+        }
+    }
+
+    Some(parts.join("/"))
+}
+
+// Hands every Markdown image URL to `resolve` and keeps the original when it returns None
+pub fn rewrite_images(body: &str, mut resolve: impl FnMut(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(body.len());
+    let mut rest = body;
+
+    while let Some(start) = rest.find("![") {
+        let url_start = match rest[start..].find("](") {
+            Some(offset) => start + offset + 2,
+            None => break,
+        };
+        output.push_str(&rest[..url_start]);
+        rest = &rest[url_start..];
+
+        let url_len = rest.find(|c: char| c.is_whitespace() || c == ')').unwrap_or(rest.len());
+        let url = &rest[..url_len];
+        output.push_str(&resolve(url).unwrap_or(url.to_string()));
+        rest = &rest[url_len..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+// The declared size can lie, so the read itself stops past the limit as well
+fn read_entry(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> Result<Vec<u8>, ImportError> {
+    let entry = archive.by_name(name)?;
+    if entry.size() > MAX_IMPORT_ENTRY_SIZE {
+        return Err(ImportError::TooLarge(name.to_string(), MAX_IMPORT_ENTRY_SIZE));
+    }
+
+    let mut bytes = Vec::new();
+    entry.take(MAX_IMPORT_ENTRY_SIZE + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > MAX_IMPORT_ENTRY_SIZE {
+        return Err(ImportError::TooLarge(name.to_string(), MAX_IMPORT_ENTRY_SIZE));
+    }
+
+    Ok(bytes)
+}
+
+// Copies an image out of the zip into the uploads folder once, later references reuse its URL
+fn import_asset(archive: &mut ZipArchive<Cursor<Vec<u8>>>, zip_path: &str, dry_run: bool, assets: &mut HashMap<String, String>, written: &mut Vec<(String, PathBuf)>) -> Result<String, ImportError> {
+    if let Some(asset_url) = assets.get(zip_path) {
+        return Ok(asset_url.clone());
+    }
+
+    let bytes = read_entry(archive, zip_path)?;
+    let filename = sanitize(Path::new(zip_path).file_name().unwrap_or_default().to_string_lossy());
+    let asset_name = if dry_run {
+        filename
+    } else {
+        let filepath = unique_upload_path(&filename);
+        fs::write(&filepath, bytes)?;
+        written.push((zip_path.to_string(), filepath.clone()));
+        filepath.file_name().unwrap_or_default().to_string_lossy().to_string()
+    };
+
+    let asset_url = format!("/assets/{}", asset_name);
+    assets.insert(zip_path.to_string(), asset_url.clone());
+
+    Ok(asset_url)
+}
+
+pub fn find_or_create_category(category_name: &str, category_slug: &str, warnings: &mut Vec<String>, conn: &mut DBPooledConnection) -> Result<i32, Error> {
+    use crate::schema::post_categories::dsl::*;
+
+    let category_slug = match slugify(category_slug) {
+        generated if generated.is_empty() => slugify(DEFAULT_CATEGORY),
+        generated => generated,
+    };
+
+    let found = post_categories
+        .filter(slug.eq(&category_slug))
+        .select((id, deleted_at))
+        .first::<(i32, Option<NaiveDateTime>)>(conn)
+        .optional()?;
+
+    match found {
+        Some((found_id, found_deleted_at)) => {
+            if found_deleted_at.is_some() {
+                warnings.push(format!("category {} is deleted, the post stays hidden until it is restored", category_slug));
+            }
+            Ok(found_id)
+        }
+        None => {
+            warnings.push(format!("created category {}", category_slug));
+            diesel::insert_into(post_categories)
+                .values((
+                    name.eq(category_name),
+                    slug.eq(&category_slug),
+                    description.eq(Some("".to_string())),
+                    published.eq(true),
+                    created_at.eq(Utc::now().naive_utc()),
+                    updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .returning(id)
+                .get_result(conn)
+        }
+    }
+}
+
+// Images copied for a file whose post isn't saved are removed again, later files copy them anew
+fn import_file(markdown_path: &str, archive: &mut ZipArchive<Cursor<Vec<u8>>>, options: &ImportOptions, assets: &mut HashMap<String, String>, report: &mut ImportFileReport, conn: &mut DBPooledConnection) -> Result<PostDB, ImportError> {
+    let mut written = Vec::new();
+    let result = import_post(markdown_path, archive, options, assets, &mut written, report, conn);
+
+    if result.is_err() {
+        for (zip_path, filepath) in written {
+            let _ = fs::remove_file(filepath);
+            assets.remove(&zip_path);
+        }
+    }
+
+    result
+}
+
+fn import_post(markdown_path: &str, archive: &mut ZipArchive<Cursor<Vec<u8>>>, options: &ImportOptions, assets: &mut HashMap<String, String>, written: &mut Vec<(String, PathBuf)>, report: &mut ImportFileReport, conn: &mut DBPooledConnection) -> Result<PostDB, ImportError> {
+    let text = String::from_utf8(read_entry(archive, markdown_path)?)
+        .map_err(|_| ImportError::FrontMatter("file isn't valid UTF-8".to_string()))?;
+    let (front_matter, body) = split_front_matter(&text)?;
+
+    let post_title = front_matter.title
+        .filter(|post_title| !post_title.trim().is_empty())
+        .ok_or(ImportError::FrontMatter("title is required".to_string()))?;
+    let post_date = match front_matter.date {
+        Some(value) => parse_date(&value).ok_or(ImportError::FrontMatter(format!("unrecognized date {}", value)))?,
+        None => Utc::now().naive_utc(),
+    };
+    let post_tags = match front_matter.tags {
+        Some(FrontMatterTags::List(list)) => list.join(", "),
+        Some(FrontMatterTags::Text(text)) => text,
+        None => "".to_string(),
+    };
+
+    let mut asset_error = None;
+    let content = rewrite_images(&body, |url| {
+        if !is_relative_reference(url) {
+            return None;
+        }
+        let zip_path = match resolve_zip_path(markdown_path, url) {
+            Some(zip_path) => zip_path,
+            None => {
+                report.warnings.push(format!("image {} points outside the zip", url));
+                return None;
+            }
+        };
+        match import_asset(archive, &zip_path, options.dry_run, assets, written) {
+            Ok(asset_url) => Some(asset_url),
+            Err(ImportError::Zip(_)) => {
+                report.warnings.push(format!("image {} isn't in the zip", url));
+                None
+            }
+            Err(e) => {
+                asset_error = Some(e);
+                None
+            }
+        }
+    });
+    if let Some(e) = asset_error {
+        return Err(e);
+    }
+
+    conn.transaction(|conn| {
+        let category_name = front_matter.category.unwrap_or(DEFAULT_CATEGORY.to_string());
+        let post = PostDB {
+            id: 1,
+            title: post_title,
+            subtitle: Some(front_matter.subtitle.unwrap_or("".to_string())),
+            slug: slugify(&front_matter.slug.unwrap_or_default()),
+            content,
+            category_id: find_or_create_category(&category_name, &category_name, &mut report.warnings, conn)?,
+            tags: Some(post_tags),
+            author_id: options.author_id,
+            created_at: post_date,
+            updated_at: post_date,
+            deleted_at: None,
+            published: front_matter.published.unwrap_or(false),
+            cover_image: None,
+            meta_title: None,
+            meta_description: None,
+            canonical_url: None,
+            og_image: None,
+            pin_scope: None,
+            pinned_until: None,
+            featured: false,
+            featured_order: None,
+            excerpt: None,
+            content_blocks: None,
+        };
+
+        let inserted_post = create_post(post, None, conn)?;
+        report.slug = Some(inserted_post.slug.clone());
+
+        // A dry run goes through every insert for real and rolls them back here
+        if options.dry_run {
+            return Err(ImportError::DryRun);
+        }
+
+        Ok(inserted_post)
+    })
+}
+
+// Imports every .md file of a zip, each file in its own transaction so one bad file doesn't stop the rest
+pub fn import_markdown_zip(bytes: Vec<u8>, options: &ImportOptions, conn: &mut DBPooledConnection) -> Result<ImportReport, ImportError> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+
+    let mut markdown_paths: Vec<String> = archive
+        .file_names()
+        .filter(|name| name.to_lowercase().ends_with(".md") && !name.starts_with("__MACOSX/"))
+        .map(|name| name.to_string())
+        .collect();
+    markdown_paths.sort();
+
+    let mut assets: HashMap<String, String> = HashMap::new();
+    let mut files = Vec::new();
+    for markdown_path in markdown_paths {
+        let mut report = ImportFileReport {
+            file: markdown_path.clone(),
+            success: false,
+            action: None,
+            post_id: None,
+            slug: None,
+            error: None,
+            warnings: Vec::new(),
+        };
+
+        match import_file(&markdown_path, &mut archive, options, &mut assets, &mut report, conn) {
+            Ok(inserted_post) => {
+                report.success = true;
+                report.action = Some("created".to_string());
+                report.post_id = Some(inserted_post.id);
+            }
+            Err(ImportError::DryRun) => {
+                report.success = true;
+                report.action = Some("created".to_string());
+            }
+            Err(e) => {
+                report.slug = None;
+                report.error = Some(e.to_string());
+            }
+        }
+        files.push(report);
+    }
+
+    let imported = files.iter().filter(|file| file.success).count();
+    Ok(ImportReport {
+        dry_run: options.dry_run,
+        imported,
+        failed: files.len() - imported,
+        files
+    })
+}
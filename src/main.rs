@@ -22,6 +22,15 @@ use crate::controller::hobby;
 use crate::controller::setting;
 use crate::controller::image;
 use crate::controller::contact;
+use crate::controller::view;
+use crate::controller::comment;
+use crate::controller::feed;
+use crate::controller::sitemap;
+use crate::controller::series;
+use crate::controller::translation;
+use crate::controller::reaction;
+use crate::controller::import;
+use crate::controller::export;
 
 mod constants;
 mod response;
@@ -30,6 +39,15 @@ mod schema;
 mod middleware;
 mod controller;
 mod errors;
+mod render;
+mod shortcode;
+mod blocks;
+mod slug;
+mod ordering;
+mod sort;
+mod importer;
+mod wxr;
+mod cli;
 
 pub type DBPool = Pool<ConnectionManager<PgConnection>>;
 pub type DBPooledConnection = PooledConnection<ConnectionManager<PgConnection>>;
@@ -52,30 +70,69 @@ async fn main() -> io::Result<()> {
         .finish()
         .unwrap();
 
+    // Anonymous reactions get a stricter budget on top of the global limit
+    let reaction_governor_conf = GovernorConfigBuilder::default()
+        .requests_per_minute(30)
+        .burst_size(10)
+        .finish()
+        .unwrap();
+
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL");
     let manager = ConnectionManager::<PgConnection>::new(database_url);
     let pool = r2d2::Pool::builder()
         .build(manager)
         .expect("Failed to create pool");
 
+    let args: Vec<String> = env::args().skip(1).collect();
+    if !args.is_empty() {
+        return cli::run(&args, &pool);
+    }
+
+    // Visitor hashes of views and reactions can't be unsalted
+    env::var("VISITOR_HASH_SALT").ok().filter(|salt| !salt.is_empty()).expect("VISITOR_HASH_SALT");
+
     let _ = HttpServer::new(move || {
         App::new()
             .data(pool.clone())
             .wrap(Governor::new(&governor_conf))
             .wrap(WebMiddleware::Logger::default())
             .service(image::serve)
+            .service(feed::rss)
+            .service(feed::atom)
+            .service(feed::json)
+            .service(feed::category_rss)
+            .service(feed::category_atom)
+            .service(sitemap::index)
+            .service(sitemap::page)
             .service(
                 web::scope("/pub")
                 .service(login::login)
                 .service(user::get)
                 //.service(user::create)
                 .service(post::active)
+                .service(post::archive)
+                .service(post::archive_month)
+                .service(post::featured)
+                .service(view::popular)
                 .service(post::get_by_slug)
+                .service(post::author_posts)
+                .service(comment::submit)
+                .service(comment::thread)
+                .service(series::public)
+                .service(postcat::tree)
+                .service(postcat::public)
                 .service(project::active)
+                .service(tech::public)
                 .service(hobby::active)
                 .service(setting::get)
                 .service(image::get)
                 .service(contact::send)
+                .service(
+                    web::scope("")
+                    .wrap(Governor::new(&reaction_governor_conf))
+                    .service(reaction::add)
+                    .service(reaction::remove)
+                )
             )
             .service(
                 web::scope("/pro")
@@ -91,6 +148,28 @@ async fn main() -> io::Result<()> {
                 .service(post::update)
                 .service(post::delete)
                 .service(post::restore)
+                .service(post::pin)
+                .service(post::unpin)
+                .service(post::reorder_featured)
+                .service(view::stats)
+                .service(translation::all)
+                .service(translation::save)
+                .service(translation::delete)
+                .service(reaction::stats)
+                .service(import::markdown)
+                .service(export::posts)
+                .service(comment::all)
+                .service(comment::get)
+                .service(comment::moderate)
+                .service(comment::delete)
+                .service(comment::restore)
+                .service(series::all)
+                .service(series::get)
+                .service(series::create)
+                .service(series::update)
+                .service(series::reorder)
+                .service(series::delete)
+                .service(series::restore)
                 .service(postcat::all)
                 .service(postcat::active)
                 .service(postcat::get)
@@ -98,12 +177,14 @@ async fn main() -> io::Result<()> {
                 .service(postcat::update)
                 .service(postcat::delete)
                 .service(postcat::restore)
+                .service(postcat::merge)
                 .service(project::all)
                 .service(project::get)
                 .service(project::create)
                 .service(project::update)
                 .service(project::delete)
                 .service(project::restore)
+                .service(project::reorder)
                 .service(tech::all)
                 .service(tech::get)
                 .service(tech::create)
@@ -122,6 +203,7 @@ async fn main() -> io::Result<()> {
                 .service(hobby::update)
                 .service(hobby::delete)
                 .service(hobby::restore)
+                .service(hobby::reorder)
                 .service(setting::all)
                 .service(setting::create)
                 .service(setting::update)
@@ -1,4 +1,4 @@
-use diesel::{Insertable, Selectable, Queryable};
+use diesel::{Insertable, Selectable, Queryable, Identifiable, Associations};
 use chrono::{Utc, NaiveDateTime, NaiveDate, TimeZone};
 use serde::{Serialize};
 use uuid::Uuid;
@@ -15,6 +15,11 @@ use crate::schema::hobbies;
 use crate::schema::settings;
 use crate::schema::projects_techs;
 use crate::schema::contacts;
+use crate::schema::post_daily_views;
+use crate::schema::comments;
+use crate::schema::series;
+use crate::schema::post_authors;
+use crate::schema::post_translations;
 
 use crate::response::*;
 
@@ -81,6 +86,7 @@ pub struct PostDB {
     pub title: String,
     pub subtitle: Option<String>,
     pub slug: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub content: String,
     pub tags: Option<String>,
     pub author_id: Uuid,
@@ -89,6 +95,27 @@ pub struct PostDB {
     pub deleted_at: Option<NaiveDateTime>,
     pub published: bool,
     pub category_id: i32,
+    pub cover_image: Option<String>,
+    pub meta_title: Option<String>,
+    pub meta_description: Option<String>,
+    pub canonical_url: Option<String>,
+    pub og_image: Option<String>,
+    pub pin_scope: Option<String>,
+    pub pinned_until: Option<NaiveDateTime>,
+    pub featured: bool,
+    pub featured_order: Option<i32>,
+    pub excerpt: Option<String>,
+    pub content_blocks: Option<serde_json::Value>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Serialize, Debug)]
+#[diesel(table_name = post_authors)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PostAuthorDB {
+    pub post_id: i32,
+    pub user_id: Uuid,
+    pub role: String,
+    pub author_order: i32,
 }
 
 #[derive(Queryable, Selectable, Insertable, Serialize, Debug)]
@@ -102,7 +129,8 @@ pub struct PostCatDB {
     pub published: bool,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
-    pub deleted_at: Option<NaiveDateTime>
+    pub deleted_at: Option<NaiveDateTime>,
+    pub parent_id: Option<i32>
 }
 
 impl PostCatDB {
@@ -115,12 +143,13 @@ impl PostCatDB {
             published: self.published.clone(),
             created_at: Utc.from_utc_datetime(&self.created_at),
             updated_at: Utc.from_utc_datetime(&self.updated_at),
-            deleted_at: self.deleted_at.map(|dt| Utc.from_utc_datetime(&dt))
+            deleted_at: self.deleted_at.map(|dt| Utc.from_utc_datetime(&dt)),
+            parent_id: self.parent_id
         }
     }
 }
 
-#[derive(Queryable, Selectable, Insertable, Serialize, Debug)]
+#[derive(Queryable, Selectable, Insertable, Identifiable, Serialize, Debug)]
 #[diesel(table_name = projects)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct ProjectDB {
@@ -167,6 +196,7 @@ pub struct TechDB {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub deleted_at: Option<NaiveDateTime>,
+    pub slug: String,
 }
 
 impl TechDB {
@@ -178,12 +208,16 @@ impl TechDB {
             created_at: Utc.from_utc_datetime(&self.created_at),
             updated_at: Utc.from_utc_datetime(&self.updated_at),
             deleted_at: self.deleted_at.map(|dt| Utc.from_utc_datetime(&dt)),
+            slug: self.slug.clone(),
         }
     }
 }
 
-#[derive(Queryable, Selectable, Insertable, Serialize, Debug)]
+#[derive(Queryable, Selectable, Insertable, Identifiable, Associations, Serialize, Debug)]
 #[diesel(table_name = projects_techs)]
+#[diesel(primary_key(project_id, tech_id))]
+#[diesel(belongs_to(ProjectDB, foreign_key = project_id))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct ProjectTechDB {
     pub project_id: i32,
     pub tech_id: i32,
@@ -309,4 +343,59 @@ impl ContactDB {
             ip_address: self.ip_address.clone(),
         }
     }
-}
\ No newline at end of file
+}
+
+#[derive(Queryable, Selectable, Insertable, Serialize, Debug)]
+#[diesel(table_name = post_daily_views)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PostDailyViewDB {
+    pub post_id: i32,
+    pub view_date: NaiveDate,
+    pub views: i32,
+}
+
+#[derive(Queryable, Selectable, Insertable, Serialize, Debug)]
+#[diesel(table_name = comments)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct CommentDB {
+    pub id: i32,
+    pub post_id: i32,
+    pub parent_id: Option<i32>,
+    pub name: String,
+    pub email: String,
+    pub content: String,
+    pub status: String,
+    pub ip_address: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Serialize, Debug)]
+#[diesel(table_name = series)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct SeriesDB {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+    pub published: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Serialize, Debug)]
+#[diesel(table_name = post_translations)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PostTranslationDB {
+    pub id: i32,
+    pub post_id: i32,
+    pub locale: String,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub slug: String,
+    pub content: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
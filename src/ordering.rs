@@ -0,0 +1,84 @@
+use diesel::result::Error;
+use diesel::sql_types::{Array, BigInt, Bool, Integer};
+use diesel::{Connection, QueryableByName, RunQueryDsl};
+use std::collections::HashSet;
+
+use crate::DBPooledConnection;
+use crate::constants::{HOBBY_ORDER_LOCK, PROJECT_ORDER_LOCK};
+use crate::errors::ReorderError;
+
+// Tables whose rows are shown by their `order` column, highest first
+#[derive(Debug, Clone, Copy)]
+pub enum Ordered {
+    Projects,
+    Hobbies
+}
+
+impl Ordered {
+    fn table(&self) -> &'static str {
+        match self {
+            Ordered::Projects => "projects",
+            Ordered::Hobbies => "hobbies",
+        }
+    }
+
+    fn lock_key(&self) -> i64 {
+        match self {
+            Ordered::Projects => PROJECT_ORDER_LOCK,
+            Ordered::Hobbies => HOBBY_ORDER_LOCK,
+        }
+    }
+}
+
+#[derive(QueryableByName)]
+struct OrderedRow {
+    #[diesel(sql_type = Integer)]
+    id: i32,
+    #[diesel(sql_type = Bool)]
+    deleted: bool
+}
+
+// Held until the transaction ends, so creates and reorders never hand out the same order
+pub fn lock_ordering(ordered: Ordered, conn: &mut DBPooledConnection) -> Result<usize, Error> {
+    diesel::sql_query("SELECT pg_advisory_xact_lock($1)")
+        .bind::<BigInt, _>(ordered.lock_key())
+        .execute(conn)
+}
+
+// Renumbers every row from the given ids, the first listed gets the highest order and shows first
+pub fn reorder(ordered: Ordered, ordered_ids: &[i32], conn: &mut DBPooledConnection) -> Result<usize, ReorderError> {
+    conn.transaction(|conn| {
+        lock_ordering(ordered, conn)?;
+
+        let rows: Vec<OrderedRow> = diesel::sql_query(format!(
+            "SELECT id, deleted_at IS NOT NULL AS deleted FROM {} ORDER BY \"order\" DESC",
+            ordered.table()
+        ))
+        .load(conn)?;
+
+        // Partial lists would leave the rest interleaved with the new orders
+        let listed_ids: HashSet<i32> = rows.iter().filter(|row| !row.deleted).map(|row| row.id).collect();
+        let requested_ids: HashSet<i32> = ordered_ids.iter().copied().collect();
+        if requested_ids.len() != ordered_ids.len() || requested_ids != listed_ids {
+            return Err(ReorderError::Mismatch);
+        }
+
+        // Deleted ones go below the listed ones so a restore doesn't collide
+        let all_ids: Vec<i32> = ordered_ids
+            .iter()
+            .copied()
+            .chain(rows.iter().filter(|row| row.deleted).map(|row| row.id))
+            .collect();
+        let orders: Vec<i32> = (1..=all_ids.len() as i32).rev().collect();
+
+        diesel::sql_query(format!(
+            "UPDATE {} AS ordered SET \"order\" = numbered.position FROM unnest($1, $2) AS numbered(id, position) WHERE ordered.id = numbered.id",
+            ordered.table()
+        ))
+        .bind::<Array<Integer>, _>(&all_ids)
+        .bind::<Array<Integer>, _>(&orders)
+        .execute(conn)?;
+
+        Ok(ordered_ids.len())
+    })
+}
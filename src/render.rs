@@ -0,0 +1,70 @@
+use pulldown_cmark::{html, Event, Options, Parser, Tag, TagEnd};
+
+pub fn markdown_to_html(markdown: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_FOOTNOTES);
+
+    let parser = Parser::new_ext(markdown, options);
+    let mut rendered = String::new();
+    html::push_html(&mut rendered, parser);
+
+    rendered
+}
+
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Plain text of the prose in a Markdown document, code blocks and image alt texts left out
+pub fn markdown_to_text(markdown: &str) -> String {
+    let mut text = String::new();
+    let mut skipping = 0;
+
+    for event in Parser::new_ext(markdown, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH) {
+        match event {
+            Event::Start(Tag::CodeBlock(_) | Tag::Image { .. }) => skipping += 1,
+            Event::End(TagEnd::CodeBlock | TagEnd::Image) => skipping -= 1,
+            Event::Text(fragment) | Event::Code(fragment) if skipping == 0 => text.push_str(&fragment),
+            Event::SoftBreak | Event::HardBreak | Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::TableCell) => text.push(' '),
+            _ => {}
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+// Cuts text to at most max_chars, preferring the last full sentence and otherwise the last whole word
+pub fn excerpt(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let cut: String = text.chars().take(max_chars).collect();
+    let sentence_end = cut
+        .char_indices()
+        .filter(|(index, c)| matches!(c, '.' | '!' | '?') && text[*index + c.len_utf8()..].starts_with(' '))
+        .map(|(index, c)| index + c.len_utf8())
+        .next_back();
+
+    match sentence_end {
+        // A lone first sentence shorter than a third of the room reads worse than a cut one
+        Some(end) if end >= cut.len() / 3 => cut[..end].to_string(),
+        _ => {
+            let end = cut.rfind(' ').unwrap_or(cut.len());
+            format!("{}…", cut[..end].trim_end_matches(|c: char| c.is_ascii_punctuation()))
+        }
+    }
+}
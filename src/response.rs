@@ -73,7 +73,8 @@ pub struct PostCatResponse {
     pub published: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
-    pub deleted_at: Option<DateTime<Utc>>
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub parent_id: Option<i32>
 }
 
 #[derive(Debug, Serialize)]
@@ -99,7 +100,8 @@ pub struct TechResponse {
     pub icon: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
-    pub deleted_at: Option<DateTime<Utc>>
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub slug: String
 }
 
 #[derive(Debug, Serialize)]
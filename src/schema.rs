@@ -1,5 +1,25 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    comments (id) {
+        id -> Int4,
+        post_id -> Int4,
+        parent_id -> Nullable<Int4>,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 255]
+        email -> Varchar,
+        content -> Text,
+        #[max_length = 20]
+        status -> Varchar,
+        #[max_length = 45]
+        ip_address -> Nullable<Varchar>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     contacts (id) {
         id -> Int4,
@@ -34,6 +54,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    post_authors (post_id, user_id) {
+        post_id -> Int4,
+        user_id -> Uuid,
+        #[max_length = 20]
+        role -> Varchar,
+        author_order -> Int4,
+    }
+}
+
 diesel::table! {
     post_categories (id) {
         id -> Int4,
@@ -46,6 +76,66 @@ diesel::table! {
         created_at -> Timestamp,
         updated_at -> Timestamp,
         deleted_at -> Nullable<Timestamp>,
+        parent_id -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    post_daily_views (post_id, view_date) {
+        post_id -> Int4,
+        view_date -> Date,
+        views -> Int4,
+    }
+}
+
+diesel::table! {
+    post_reactions (id) {
+        id -> Int4,
+        post_id -> Int4,
+        #[max_length = 20]
+        reaction -> Varchar,
+        #[max_length = 64]
+        visitor_hash -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    post_slugs (id) {
+        id -> Int4,
+        post_id -> Int4,
+        #[max_length = 255]
+        slug -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    post_translations (id) {
+        id -> Int4,
+        post_id -> Int4,
+        #[max_length = 10]
+        locale -> Varchar,
+        #[max_length = 255]
+        title -> Varchar,
+        #[max_length = 255]
+        subtitle -> Nullable<Varchar>,
+        #[max_length = 255]
+        slug -> Varchar,
+        content -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    post_views (id) {
+        id -> Int4,
+        post_id -> Int4,
+        #[max_length = 64]
+        visitor_hash -> Varchar,
+        viewed_on -> Date,
+        created_at -> Timestamp,
     }
 }
 
@@ -66,6 +156,23 @@ diesel::table! {
         deleted_at -> Nullable<Timestamp>,
         published -> Bool,
         category_id -> Int4,
+        #[max_length = 255]
+        cover_image -> Nullable<Varchar>,
+        #[max_length = 70]
+        meta_title -> Nullable<Varchar>,
+        #[max_length = 160]
+        meta_description -> Nullable<Varchar>,
+        #[max_length = 255]
+        canonical_url -> Nullable<Varchar>,
+        #[max_length = 255]
+        og_image -> Nullable<Varchar>,
+        #[max_length = 20]
+        pin_scope -> Nullable<Varchar>,
+        pinned_until -> Nullable<Timestamp>,
+        featured -> Bool,
+        featured_order -> Nullable<Int4>,
+        excerpt -> Nullable<Text>,
+        content_blocks -> Nullable<Jsonb>,
     }
 }
 
@@ -114,6 +221,29 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    series (id) {
+        id -> Int4,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 255]
+        slug -> Varchar,
+        description -> Nullable<Text>,
+        published -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    series_posts (series_id, post_id) {
+        series_id -> Int4,
+        post_id -> Int4,
+        part_order -> Int4,
+    }
+}
+
 diesel::table! {
     settings (id) {
         id -> Int4,
@@ -138,6 +268,8 @@ diesel::table! {
         created_at -> Timestamp,
         updated_at -> Timestamp,
         deleted_at -> Nullable<Timestamp>,
+        #[max_length = 255]
+        slug -> Varchar,
     }
 }
 
@@ -164,21 +296,50 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    wordpress_posts (guid) {
+        guid -> Text,
+        post_id -> Int4,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::joinable!(comments -> posts (post_id));
+diesel::joinable!(post_authors -> posts (post_id));
+diesel::joinable!(post_authors -> users (user_id));
+diesel::joinable!(post_daily_views -> posts (post_id));
+diesel::joinable!(post_reactions -> posts (post_id));
+diesel::joinable!(post_slugs -> posts (post_id));
+diesel::joinable!(post_translations -> posts (post_id));
+diesel::joinable!(post_views -> posts (post_id));
 diesel::joinable!(posts -> post_categories (category_id));
 diesel::joinable!(posts -> users (author_id));
 diesel::joinable!(projects_techs -> projects (project_id));
 diesel::joinable!(projects_techs -> techs (tech_id));
+diesel::joinable!(series_posts -> posts (post_id));
+diesel::joinable!(series_posts -> series (series_id));
 diesel::joinable!(users -> roles (role_id));
+diesel::joinable!(wordpress_posts -> posts (post_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    comments,
     contacts,
     hobbies,
+    post_authors,
     post_categories,
+    post_daily_views,
+    post_reactions,
+    post_slugs,
+    post_translations,
+    post_views,
     posts,
     projects,
     projects_techs,
     roles,
+    series,
+    series_posts,
     settings,
     techs,
     users,
+    wordpress_posts,
 );
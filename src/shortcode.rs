@@ -0,0 +1,286 @@
+use diesel::result::Error;
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods};
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+use crate::DBPooledConnection;
+use crate::controller::image::is_uploaded_asset;
+use crate::controller::setting::SiteSettings;
+use crate::render::{escape_xml, markdown_to_html};
+
+const SHORTCODE_KINDS: [&str; 3] = ["project", "image", "post"];
+
+// A `{{kind:target key="value"}}` reference found in post content
+#[derive(Debug, Clone)]
+pub struct Shortcode {
+    pub raw: String,
+    pub kind: String,
+    pub target: String,
+    pub attrs: BTreeMap<String, String>,
+    pub start: usize,
+    pub end: usize
+}
+
+// What a shortcode stands for once its target is looked up
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Embed {
+    Project {
+        id: i32,
+        title: String,
+        url: String,
+        source: Option<String>,
+        demo: Option<String>
+    },
+    Image {
+        src: String,
+        alt: String,
+        caption: Option<String>
+    },
+    Post {
+        slug: String,
+        url: String,
+        title: String,
+        subtitle: Option<String>
+    }
+}
+
+impl Embed {
+    pub fn to_html(&self) -> String {
+        match self {
+            Embed::Project { title, url, source, demo, .. } => {
+                let mut html = format!("<aside class=\"embed-project\"><a href=\"{}\">{}</a>", escape_xml(url), escape_xml(title));
+                if let Some(demo) = demo {
+                    html.push_str(&format!(" <a href=\"{}\">Demo</a>", escape_xml(demo)));
+                }
+                if let Some(source) = source {
+                    html.push_str(&format!(" <a href=\"{}\">Source</a>", escape_xml(source)));
+                }
+                html.push_str("</aside>");
+                html
+            }
+            Embed::Image { src, alt, caption } => match caption {
+                Some(caption) => format!("<figure><img src=\"{}\" alt=\"{}\"><figcaption>{}</figcaption></figure>", escape_xml(src), escape_xml(alt), escape_xml(caption)),
+                None => format!("<figure><img src=\"{}\" alt=\"{}\"></figure>", escape_xml(src), escape_xml(alt)),
+            },
+            Embed::Post { url, title, .. } => format!("<a class=\"embed-post\" href=\"{}\">{}</a>", escape_xml(url), escape_xml(title)),
+        }
+    }
+}
+
+// Splits `target key="value" other=word` into the target and its attributes
+fn parse_arguments(arguments: &str) -> (String, BTreeMap<String, String>) {
+    let arguments = arguments.trim();
+    let (target, mut rest) = arguments.split_once(char::is_whitespace).unwrap_or((arguments, ""));
+    let mut attrs = BTreeMap::new();
+
+    while let Some((key, after_key)) = rest.trim_start().split_once('=') {
+        let (value, after_value) = match after_key.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => after_key.split_once(char::is_whitespace).unwrap_or((after_key, "")),
+        };
+        attrs.insert(key.trim().to_lowercase(), value.to_string());
+        rest = after_value;
+    }
+
+    (target.to_string(), attrs)
+}
+
+// Byte ranges of the code spans and code blocks, shortcodes in them are shown as written
+fn code_ranges(content: &str) -> Vec<Range<usize>> {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_FOOTNOTES;
+
+    Parser::new_ext(content, options)
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Code(_) | Event::Start(Tag::CodeBlock(_)) => Some(range),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn parse_shortcodes(content: &str) -> Vec<Shortcode> {
+    let code = code_ranges(content);
+    let mut shortcodes = Vec::new();
+    let mut offset = 0;
+
+    while let Some(open) = content[offset..].find("{{") {
+        let start = offset + open;
+        if let Some(range) = code.iter().find(|range| range.contains(&start)) {
+            offset = range.end;
+            continue;
+        }
+
+        let close = match content[start..].find("}}") {
+            Some(close) => start + close + 2,
+            None => break,
+        };
+
+        let inner = &content[start + 2..close - 2];
+        if let Some((kind, arguments)) = inner.split_once(':') {
+            let (target, attrs) = parse_arguments(arguments);
+            shortcodes.push(Shortcode {
+                raw: content[start..close].to_string(),
+                kind: kind.trim().to_lowercase(),
+                target,
+                attrs,
+                start,
+                end: close
+            });
+        }
+        offset = close;
+    }
+
+    shortcodes
+}
+
+fn load_projects(project_ids: &[i32], site: &SiteSettings, conn: &mut DBPooledConnection) -> Result<HashMap<i32, Embed>, Error> {
+    use crate::schema::projects::dsl::*;
+
+    if project_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let found = projects
+        .filter(id.eq_any(project_ids))
+        .filter(published.eq(true))
+        .filter(deleted_at.is_null())
+        .select((id, title, source, demo))
+        .load::<(i32, String, Option<String>, Option<String>)>(conn)?;
+
+    Ok(found
+        .into_iter()
+        .map(|(project_id, project_title, project_source, project_demo)| (project_id, Embed::Project {
+            id: project_id,
+            title: project_title,
+            url: site.project_url(project_id),
+            source: project_source,
+            demo: project_demo
+        }))
+        .collect())
+}
+
+fn load_posts(post_slugs: &[&str], site: &SiteSettings, conn: &mut DBPooledConnection) -> Result<HashMap<String, Embed>, Error> {
+    use crate::schema::posts::dsl::*;
+
+    if post_slugs.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let found = posts
+        .filter(slug.eq_any(post_slugs))
+        .filter(published.eq(true))
+        .filter(deleted_at.is_null())
+        .select((slug, title, subtitle))
+        .load::<(String, String, Option<String>)>(conn)?;
+
+    Ok(found
+        .into_iter()
+        .map(|(post_slug, post_title, post_subtitle)| (post_slug.clone(), Embed::Post {
+            url: site.post_url(&post_slug),
+            slug: post_slug,
+            title: post_title,
+            subtitle: post_subtitle.filter(|post_subtitle| !post_subtitle.is_empty())
+        }))
+        .collect())
+}
+
+fn resolve_image(shortcode: &Shortcode, site: &SiteSettings) -> Option<Embed> {
+    let asset = format!("/assets/{}", shortcode.target.trim_start_matches('/').trim_start_matches("assets/"));
+    if !is_uploaded_asset(&asset) {
+        return None;
+    }
+
+    let caption = shortcode.attrs.get("caption").cloned().filter(|caption| !caption.is_empty());
+    Some(Embed::Image {
+        src: site.absolute_url(&asset),
+        alt: shortcode.attrs.get("alt").cloned().or(caption.clone()).unwrap_or_default(),
+        caption
+    })
+}
+
+// The projects and posts referenced by a batch of contents, looked up with one query each
+pub struct Embeds {
+    projects: HashMap<i32, Embed>,
+    posts: HashMap<String, Embed>
+}
+
+impl Embeds {
+    pub fn load(contents: &[&str], site: &SiteSettings, conn: &mut DBPooledConnection) -> Result<Embeds, Error> {
+        let shortcodes: Vec<Shortcode> = contents.iter().flat_map(|content| parse_shortcodes(content)).collect();
+        let project_ids: Vec<i32> = shortcodes
+            .iter()
+            .filter(|shortcode| shortcode.kind == "project")
+            .filter_map(|shortcode| shortcode.target.parse().ok())
+            .collect();
+        let post_slugs: Vec<&str> = shortcodes
+            .iter()
+            .filter(|shortcode| shortcode.kind == "post")
+            .map(|shortcode| shortcode.target.as_str())
+            .collect();
+
+        Ok(Embeds {
+            projects: load_projects(&project_ids, site, conn)?,
+            posts: load_posts(&post_slugs, site, conn)?
+        })
+    }
+
+    // The embed a shortcode stands for, or the warning explaining why it has none
+    pub fn resolve(&self, shortcode: &Shortcode, site: &SiteSettings) -> Result<Embed, String> {
+        let embed = match shortcode.kind.as_str() {
+            "project" => shortcode.target.parse::<i32>().ok().and_then(|project_id| self.projects.get(&project_id).cloned()),
+            "post" => self.posts.get(&shortcode.target).cloned(),
+            "image" => resolve_image(shortcode, site),
+            _ => return Err(format!("Unknown shortcode {}", shortcode.raw)),
+        };
+
+        embed.ok_or(format!("Shortcode {} doesn't match a published {}", shortcode.raw, shortcode.kind))
+    }
+}
+
+pub fn shortcode_warnings(content: &str, conn: &mut DBPooledConnection) -> Result<Vec<String>, Error> {
+    let site = SiteSettings::load(conn);
+    let embeds = Embeds::load(&[content], &site, conn)?;
+
+    Ok(parse_shortcodes(content)
+        .iter()
+        .filter_map(|shortcode| embeds.resolve(shortcode, &site).err())
+        .collect())
+}
+
+// Markdown with every shortcode swapped for its HTML, the ones that can't be resolved stay as written
+pub fn expand_shortcodes(content: &str, site: &SiteSettings, embeds: &Embeds) -> String {
+    let mut expanded = String::with_capacity(content.len());
+    let mut offset = 0;
+
+    for shortcode in parse_shortcodes(content) {
+        expanded.push_str(&content[offset..shortcode.start]);
+        match embeds.resolve(&shortcode, site) {
+            Ok(embed) => expanded.push_str(&embed.to_html()),
+            Err(_) => expanded.push_str(&shortcode.raw),
+        }
+        offset = shortcode.end;
+    }
+    expanded.push_str(&content[offset..]);
+
+    expanded
+}
+
+pub fn render_content(content: &str, site: &SiteSettings, embeds: &Embeds) -> String {
+    markdown_to_html(&expand_shortcodes(content, site, embeds))
+}
+
+pub fn strip_shortcodes(content: &str) -> String {
+    let mut stripped = String::with_capacity(content.len());
+    let mut offset = 0;
+
+    for shortcode in parse_shortcodes(content).into_iter().filter(|shortcode| SHORTCODE_KINDS.contains(&shortcode.kind.as_str())) {
+        stripped.push_str(&content[offset..shortcode.start]);
+        offset = shortcode.end;
+    }
+    stripped.push_str(&content[offset..]);
+
+    stripped
+}
@@ -0,0 +1,31 @@
+use deunicode::deunicode;
+
+// Leaves room for a numeric suffix within the VARCHAR(255) slug columns
+const SLUG_MAX_LENGTH: usize = 200;
+
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+
+    for c in deunicode(text).to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    slug.truncate(SLUG_MAX_LENGTH);
+    slug.trim_end_matches('-').to_string()
+}
+
+// Picks `base`, or the first `base-N` that isn't in `taken`
+pub fn unique_slug(base: &str, taken: &[String]) -> String {
+    if !taken.iter().any(|existing| existing == base) {
+        return base.to_string();
+    }
+
+    (2..)
+        .map(|suffix| format!("{}-{}", base, suffix))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_default()
+}
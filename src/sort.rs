@@ -0,0 +1,16 @@
+// Order of the public listings that carry a usage count, `sort=name` (default) or `sort=count`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UsageSort {
+    Name,
+    Count
+}
+
+impl UsageSort {
+    pub fn parse(sort: Option<&str>) -> Result<UsageSort, &'static str> {
+        match sort.unwrap_or("name") {
+            "name" => Ok(UsageSort::Name),
+            "count" => Ok(UsageSort::Count),
+            _ => Err("Sort must be name or count"),
+        }
+    }
+}
@@ -0,0 +1,445 @@
+use chrono::Utc;
+use diesel::result::Error;
+use diesel::{RunQueryDsl, QueryDsl, ExpressionMethods, OptionalExtension, Connection};
+use sha2::{Sha256, Digest};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use sanitize_filename::sanitize;
+use uuid::Uuid;
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use crate::DBPooledConnection;
+use crate::controller::image::{upload_candidates, unique_upload_path};
+use crate::controller::post::{create_post, free_post_slug, record_slug, set_post_authors};
+use crate::errors::ImportError;
+use crate::importer::{ImportFileReport, ImportReport, DEFAULT_CATEGORY, find_or_create_category, parse_date};
+use crate::models::PostDB;
+use crate::slug::slugify;
+
+// WXR Option Struct
+pub struct WxrOptions {
+    pub attachments_dir: PathBuf,
+    pub fallback_author_id: Option<Uuid>,
+    pub dry_run: bool
+}
+
+#[derive(Debug, Default)]
+struct WxrAuthor {
+    login: String,
+    email: String
+}
+
+#[derive(Debug, Default)]
+struct WxrCategory {
+    slug: String,
+    name: String
+}
+
+#[derive(Debug, Default)]
+struct WxrItem {
+    wp_id: String,
+    guid: String,
+    title: String,
+    creator: String,
+    content: String,
+    post_name: String,
+    post_date: String,
+    post_date_gmt: String,
+    status: String,
+    post_type: String,
+    attachment_url: String,
+    categories: Vec<WxrCategory>,
+    tags: Vec<String>
+}
+
+#[derive(Debug, Default)]
+struct WxrDocument {
+    authors: Vec<WxrAuthor>,
+    categories: Vec<WxrCategory>,
+    items: Vec<WxrItem>
+}
+
+// Class Wide Function
+
+fn attribute(element: &BytesStart, key: &str) -> String {
+    element.attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == key.as_bytes())
+        .and_then(|attr| attr.unescape_value().ok().map(|value| value.to_string()))
+        .unwrap_or_default()
+}
+
+// Walks the export once and keeps the handful of elements the import needs
+fn parse_wxr(xml: &str) -> Result<WxrDocument, ImportError> {
+    let mut reader = Reader::from_str(xml);
+    let mut document = WxrDocument::default();
+
+    let mut item: Option<WxrItem> = None;
+    let mut author: Option<WxrAuthor> = None;
+    let mut channel_category: Option<WxrCategory> = None;
+    let mut item_category = WxrCategory::default();
+    let mut item_category_domain = String::new();
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) => {
+                match element.name().as_ref() {
+                    b"item" => item = Some(WxrItem::default()),
+                    b"wp:author" => author = Some(WxrAuthor::default()),
+                    b"wp:category" => channel_category = Some(WxrCategory::default()),
+                    b"category" => {
+                        item_category_domain = attribute(&element, "domain");
+                        item_category.slug = attribute(&element, "nicename");
+                    }
+                    _ => {}
+                }
+                text.clear();
+            }
+            Event::Text(element) => match element.unescape() {
+                Ok(unescaped) => text.push_str(&unescaped),
+                Err(_) => text.push_str(&String::from_utf8_lossy(&element)),
+            },
+            Event::CData(element) => text.push_str(&String::from_utf8_lossy(&element.into_inner())),
+            Event::End(element) => {
+                let value = mem::take(&mut text).trim().to_string();
+                match (element.name().as_ref(), item.as_mut(), author.as_mut(), channel_category.as_mut()) {
+                    (b"item", Some(_), _, _) => document.items.extend(item.take()),
+                    (b"title", Some(item), _, _) => item.title = value,
+                    (b"guid", Some(item), _, _) => item.guid = value,
+                    (b"dc:creator", Some(item), _, _) => item.creator = value,
+                    (b"content:encoded", Some(item), _, _) => item.content = value,
+                    (b"wp:post_id", Some(item), _, _) => item.wp_id = value,
+                    (b"wp:post_name", Some(item), _, _) => item.post_name = value,
+                    (b"wp:post_date", Some(item), _, _) => item.post_date = value,
+                    (b"wp:post_date_gmt", Some(item), _, _) => item.post_date_gmt = value,
+                    (b"wp:status", Some(item), _, _) => item.status = value,
+                    (b"wp:post_type", Some(item), _, _) => item.post_type = value,
+                    (b"wp:attachment_url", Some(item), _, _) => item.attachment_url = value,
+                    (b"category", Some(item), _, _) => {
+                        let mut category = mem::take(&mut item_category);
+                        category.name = value;
+                        match item_category_domain.as_str() {
+                            "category" => item.categories.push(category),
+                            "post_tag" => item.tags.push(category.name),
+                            _ => {}
+                        }
+                    }
+                    (b"wp:author", _, Some(_), _) => document.authors.extend(author.take()),
+                    (b"wp:author_login", _, Some(author), _) => author.login = value,
+                    (b"wp:author_email", _, Some(author), _) => author.email = value,
+                    (b"wp:category", _, _, Some(_)) => document.categories.extend(channel_category.take()),
+                    (b"wp:category_nicename", _, _, Some(category)) => category.slug = value,
+                    (b"wp:cat_name", _, _, Some(category)) => category.name = value,
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(document)
+}
+
+// WordPress stores non-ASCII slugs percent-encoded
+fn decode_slug(post_name: &str) -> String {
+    let bytes = post_name.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = post_name.get(index + 1..index + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn file_digest(path: &Path) -> Result<Vec<u8>, io::Error> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().to_vec())
+}
+
+// Copies an attachment into the uploads folder, reusing an identical file from an earlier run
+fn copy_attachment(attachment_url: &str, options: &WxrOptions) -> Result<Option<String>, ImportError> {
+    let filename = attachment_url.rsplit('/').next().unwrap_or("");
+    let relative = attachment_url.split("/wp-content/uploads/").nth(1).unwrap_or(filename);
+
+    let source = [options.attachments_dir.join(relative), options.attachments_dir.join(filename)]
+        .into_iter()
+        .find(|source| source.is_file());
+    let source = match source {
+        Some(source) => source,
+        None => return Ok(None),
+    };
+
+    // Earlier runs left it under the name or one of the -copyN names next to it
+    let upload_name = sanitize(filename);
+    let source_digest = file_digest(&source)?;
+    for existing in upload_candidates(&upload_name).take_while(|existing| existing.exists()) {
+        if existing.is_file() && file_digest(&existing)? == source_digest {
+            return Ok(Some(format!("/assets/{}", existing.file_name().unwrap_or_default().to_string_lossy())));
+        }
+    }
+
+    let target = unique_upload_path(&upload_name);
+    if !options.dry_run {
+        fs::copy(&source, &target)?;
+    }
+
+    Ok(Some(format!("/assets/{}", target.file_name().unwrap_or_default().to_string_lossy())))
+}
+
+fn user_id_by_email(author_email: &str, conn: &mut DBPooledConnection) -> Result<Option<Uuid>, Error> {
+    use crate::schema::users::dsl::*;
+
+    users
+        .filter(email.eq(author_email))
+        .filter(deleted_at.is_null())
+        .select(id)
+        .first::<Uuid>(conn)
+        .optional()
+}
+
+// What identifies a WordPress post across exports, its guid or else its WordPress id
+fn source_key(item: &WxrItem) -> Option<String> {
+    if !item.guid.is_empty() {
+        Some(item.guid.clone())
+    } else if !item.wp_id.is_empty() {
+        Some(format!("wp-post-{}", item.wp_id))
+    } else {
+        None
+    }
+}
+
+fn imported_post_id(source: &str, conn: &mut DBPooledConnection) -> Result<Option<i32>, Error> {
+    use crate::schema::wordpress_posts::dsl::*;
+
+    wordpress_posts
+        .filter(guid.eq(source))
+        .select(post_id)
+        .first::<i32>(conn)
+        .optional()
+}
+
+// Updates only what the export carries, edits made here to the subtitle, images, SEO fields or excerpt stay
+fn update_imported_post(post: PostDB, post_id: i32, conn: &mut DBPooledConnection) -> Result<PostDB, ImportError> {
+    use crate::schema::posts::dsl::*;
+
+    let current_slug = posts.filter(id.eq(post_id)).select(slug).first::<String>(conn)?;
+    let new_slug = if current_slug == post.slug {
+        current_slug
+    } else {
+        free_post_slug(&post.slug, Some(post_id), conn)?
+    };
+
+    let updated_post: PostDB = diesel::update(posts.filter(id.eq(post_id)))
+        .set((
+            title.eq(post.title),
+            slug.eq(new_slug),
+            content.eq(post.content),
+            content_blocks.eq(None::<serde_json::Value>),
+            category_id.eq(post.category_id),
+            tags.eq(post.tags),
+            author_id.eq(post.author_id),
+            created_at.eq(post.created_at),
+            updated_at.eq(Utc::now().naive_utc()),
+            published.eq(post.published),
+        ))
+        .get_result(conn)?;
+
+    record_slug(updated_post.id, &updated_post.slug, conn)?;
+    set_post_authors(updated_post.id, updated_post.author_id, None, conn)?;
+
+    Ok(updated_post)
+}
+
+fn import_item(item: WxrItem, author_id: Uuid, attachments: &HashMap<String, String>, options: &WxrOptions, report: &mut ImportFileReport, conn: &mut DBPooledConnection) -> Result<PostDB, ImportError> {
+    let source = source_key(&item);
+    let post_date = [&item.post_date_gmt, &item.post_date]
+        .into_iter()
+        .find_map(|value| parse_date(value))
+        .unwrap_or(Utc::now().naive_utc());
+    let post_slug = match slugify(&decode_slug(&item.post_name)) {
+        decoded if decoded.is_empty() => slugify(&item.title),
+        decoded => decoded,
+    };
+
+    let mut content = item.content;
+    for (attachment_url, asset_url) in attachments {
+        content = content.replace(attachment_url, asset_url);
+    }
+
+    let mut categories = item.categories.into_iter();
+    let category = categories.next().unwrap_or(WxrCategory {
+        slug: DEFAULT_CATEGORY.to_string(),
+        name: DEFAULT_CATEGORY.to_string(),
+    });
+    for extra in categories {
+        report.warnings.push(format!("only one category is kept, dropped {}", extra.slug));
+    }
+
+    conn.transaction(|conn| {
+        let post = PostDB {
+            id: 1,
+            title: item.title,
+            subtitle: Some("".to_string()),
+            slug: post_slug.clone(),
+            content,
+            category_id: find_or_create_category(&category.name, &category.slug, &mut report.warnings, conn)?,
+            tags: Some(item.tags.join(", ")),
+            author_id,
+            created_at: post_date,
+            updated_at: post_date,
+            deleted_at: None,
+            published: item.status == "publish",
+            cover_image: None,
+            meta_title: None,
+            meta_description: None,
+            canonical_url: None,
+            og_image: None,
+            pin_scope: None,
+            pinned_until: None,
+            featured: false,
+            featured_order: None,
+            excerpt: None,
+            content_blocks: None,
+        };
+
+        // Re-running the import finds the post it made through the WordPress guid, never through a slug
+        let saved = match source.as_deref().map(|source| imported_post_id(source, conn)).transpose()?.flatten() {
+            Some(existing_id) => {
+                report.action = Some("updated".to_string());
+                update_imported_post(post, existing_id, conn)?
+            }
+            None => {
+                use crate::schema::wordpress_posts::dsl::{wordpress_posts, guid, post_id};
+
+                // A local post already holding the slug stays as it is, the import gets the next free one
+                let mut post = post;
+                post.slug = free_post_slug(&post_slug, None, conn)?;
+                if post.slug != post_slug {
+                    report.warnings.push(format!("slug {} is taken, imported as {}", post_slug, post.slug));
+                }
+
+                report.action = Some("created".to_string());
+                let created = create_post(post, None, conn)?;
+                if let Some(source) = source {
+                    diesel::insert_into(wordpress_posts)
+                        .values((guid.eq(source), post_id.eq(created.id)))
+                        .execute(conn)?;
+                }
+                created
+            }
+        };
+        report.slug = Some(saved.slug.clone());
+
+        if options.dry_run {
+            return Err(ImportError::DryRun);
+        }
+
+        Ok(saved)
+    })
+}
+
+fn empty_report(file: String) -> ImportFileReport {
+    ImportFileReport {
+        file,
+        success: false,
+        action: None,
+        post_id: None,
+        slug: None,
+        error: None,
+        warnings: Vec::new(),
+    }
+}
+
+// Imports the posts of a WordPress export, each post in its own transaction
+pub fn import_wxr(xml: &str, options: &WxrOptions, conn: &mut DBPooledConnection) -> Result<ImportReport, ImportError> {
+    let document = parse_wxr(xml)?;
+    let mut files = Vec::new();
+
+    let mut category_warnings = Vec::new();
+    if !options.dry_run {
+        for category in &document.categories {
+            find_or_create_category(&category.name, &category.slug, &mut category_warnings, conn)?;
+        }
+    }
+
+    let mut attachments: HashMap<String, String> = HashMap::new();
+    let mut posts = Vec::new();
+    for item in document.items {
+        match item.post_type.as_str() {
+            "attachment" if !item.attachment_url.is_empty() => {
+                let mut report = empty_report(item.attachment_url.clone());
+                match copy_attachment(&item.attachment_url, options) {
+                    Ok(Some(asset_url)) => {
+                        report.success = true;
+                        report.action = Some("copied".to_string());
+                        attachments.insert(item.attachment_url, asset_url);
+                    }
+                    Ok(None) => report.error = Some("attachment isn't in the attachments directory".to_string()),
+                    Err(e) => report.error = Some(e.to_string()),
+                }
+                files.push(report);
+            }
+            "post" if item.status != "trash" => posts.push(item),
+            _ => {}
+        }
+    }
+
+    let mut authors: HashMap<String, Option<Uuid>> = HashMap::new();
+    for wxr_author in document.authors {
+        let author_id = user_id_by_email(&wxr_author.email, conn)?;
+        authors.insert(wxr_author.login, author_id);
+    }
+
+    for item in posts {
+        let mut report = empty_report(format!("post {} {}", item.wp_id, item.title));
+
+        let author_id = match authors.get(&item.creator).copied().flatten().or(options.fallback_author_id) {
+            Some(author_id) => author_id,
+            None => {
+                report.error = Some(format!("no user has the email of WordPress author {}", item.creator));
+                files.push(report);
+                continue;
+            }
+        };
+
+        match import_item(item, author_id, &attachments, options, &mut report, conn) {
+            Ok(saved) => {
+                report.success = true;
+                report.post_id = Some(saved.id);
+            }
+            Err(ImportError::DryRun) => report.success = true,
+            Err(e) => {
+                report.action = None;
+                report.slug = None;
+                report.error = Some(e.to_string());
+            }
+        }
+        files.push(report);
+    }
+
+    let imported = files.iter().filter(|file| file.success).count();
+    Ok(ImportReport {
+        dry_run: options.dry_run,
+        imported,
+        failed: files.len() - imported,
+        files
+    })
+}